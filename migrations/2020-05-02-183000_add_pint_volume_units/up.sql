@@ -0,0 +1,2 @@
+ALTER TYPE VOLUMEUNIT ADD VALUE 'uk pint';
+ALTER TYPE VOLUMEUNIT ADD VALUE 'us pint';
@@ -0,0 +1,2 @@
+ALTER TABLE person DROP COLUMN password_hash;
+ALTER TABLE person DROP COLUMN username;
@@ -0,0 +1,2 @@
+ALTER TABLE person ADD COLUMN username VARCHAR UNIQUE;
+ALTER TABLE person ADD COLUMN password_hash VARCHAR;
@@ -0,0 +1,13 @@
+CREATE TABLE entry_audit_log (
+    id          SERIAL PRIMARY KEY,
+    entry_id    INTEGER     NOT NULL REFERENCES entry(id)  ON DELETE CASCADE ON UPDATE CASCADE,
+    changed_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    field_name  VARCHAR     NOT NULL,
+    old_value   TEXT            NULL,
+    new_value   TEXT            NULL,
+    changed_by  INTEGER     NOT NULL REFERENCES person(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX ON entry_audit_log (entry_id, changed_at);
+
+COMMENT ON TABLE entry_audit_log IS 'The history of changes made to each entry, one row per changed field.';
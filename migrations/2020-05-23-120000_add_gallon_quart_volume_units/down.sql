@@ -0,0 +1,2 @@
+-- Postgres does not support removing values from an enum type,
+-- so the gallon and quart units are left in place when rolling back.
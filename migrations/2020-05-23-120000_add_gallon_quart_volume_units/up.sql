@@ -0,0 +1,2 @@
+ALTER TYPE VOLUMEUNIT ADD VALUE 'gallon';
+ALTER TYPE VOLUMEUNIT ADD VALUE 'quart';
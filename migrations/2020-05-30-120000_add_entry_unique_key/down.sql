@@ -0,0 +1 @@
+DROP INDEX IF EXISTS entry_person_drink_time_key;
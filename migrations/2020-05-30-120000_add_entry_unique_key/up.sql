@@ -0,0 +1,13 @@
+-- An entry is considered a duplicate of another if the same person drank the same drink
+-- on the same date, during the same time period. This key is the conflict target used by
+-- the importer's `--upsert` mode, so that importing the same file twice skips the entries
+-- which already exist, rather than creating them again.
+--
+-- Any existing duplicates must be merged or removed before this index can be created;
+-- they can be found with:
+--
+--   SELECT person_id, drank_on, time_period, drink_id, COUNT(*)
+--   FROM entry
+--   GROUP BY person_id, drank_on, time_period, drink_id
+--   HAVING COUNT(*) > 1;
+CREATE UNIQUE INDEX entry_person_drink_time_key ON entry (person_id, drank_on, time_period, drink_id);
@@ -27,6 +27,10 @@ where
     pub status: ResponseStatus,
     pub data: Option<ApiResponseEnvelope<T>>,
     pub messages: Option<Vec<String>>,
+
+    /// The total number of items available, when `data` is a single page of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<i64>,
 }
 
 impl<T> Serialize for ApiResponseEnvelope<T>
@@ -53,6 +57,7 @@ where
             status: ResponseStatus::Success,
             data: data.map(|data| ApiResponseEnvelope(data)),
             messages: None,
+            total_count: None,
         }
     }
 
@@ -61,6 +66,7 @@ where
             status: ResponseStatus::Success,
             data: Some(ApiResponseEnvelope(data)),
             messages: None,
+            total_count: None,
         }
     }
 
@@ -70,6 +76,7 @@ where
             status: ResponseStatus::Fail,
             data: Some(ApiResponseEnvelope(data)),
             messages: None,
+            total_count: None,
         }
     }
 
@@ -79,6 +86,7 @@ where
             status: ResponseStatus::Error,
             data: Some(ApiResponseEnvelope(data)),
             messages: None,
+            total_count: None,
         }
     }
 
@@ -93,6 +101,11 @@ where
         self
     }
 
+    pub fn with_total_count(mut self, total_count: i64) -> ApiResponse<T> {
+        self.total_count = Some(total_count);
+        self
+    }
+
     pub fn add_message<S: Into<String>>(mut self, message: S) -> ApiResponse<T> {
         if self.messages.is_none() {
             self.messages = Some(Vec::new());
@@ -110,6 +123,7 @@ impl ApiResponse<()> {
             status: ResponseStatus::Error,
             data: None,
             messages: Some(vec![message.into()]),
+            total_count: None,
         }
     }
 
@@ -119,6 +133,7 @@ impl ApiResponse<()> {
             status: ResponseStatus::Fail,
             data: None,
             messages: Some(vec![message.into()]),
+            total_count: None,
         }
     }
 
@@ -128,6 +143,7 @@ impl ApiResponse<()> {
             status: ResponseStatus::Success,
             data: None,
             messages: Some(vec![message.into()]),
+            total_count: None,
         }
     }
 }
@@ -0,0 +1,237 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{Error as ActixError, FromRequest, HttpMessage, HttpRequest};
+use chrono::{Duration, Utc};
+use futures::future::{err, ok, Ready};
+use futures::Future;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+
+use crate::error::{Error, Result};
+use crate::models::Person;
+
+/// How long an issued token remains valid.
+const TOKEN_LIFETIME_HOURS: i64 = 24;
+
+/// The secret used to sign tokens, shared with request handlers as app data.
+#[derive(Clone)]
+pub struct JwtSecret(pub String);
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Claims {
+    /// The ID of the authenticated person.
+    pub sub: i32,
+
+    /// Expiration, as a unix timestamp.
+    pub exp: usize,
+}
+
+/// Create a signed token identifying the given person.
+pub fn create_token(person_id: i32, secret: &str) -> Result<String> {
+    let expires = Utc::now() + Duration::hours(TOKEN_LIFETIME_HOURS);
+    let claims = Claims {
+        sub: person_id,
+        exp: expires.timestamp() as usize,
+    };
+
+    Ok(encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )?)
+}
+
+/// Check the token's signature and expiration, returning its claims if valid.
+pub fn verify_token(token: &str, secret: &str) -> Result<Claims> {
+    let data = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )?;
+
+    Ok(data.claims)
+}
+
+/// Check the password against the person's stored hash.
+/// People without a password can not log in.
+pub fn verify_password(person: &Person, password: &str) -> Result<bool> {
+    match &person.password_hash {
+        Some(hash) => Ok(bcrypt::verify(password, hash)?),
+        None => Ok(false),
+    }
+}
+
+/// Get the token from an `Authorization: Bearer <token>` header value.
+fn parse_bearer(header: &str) -> Option<&str> {
+    let mut parts = header.splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+            Some(token.trim()).filter(|t| !t.is_empty())
+        }
+        _ => None,
+    }
+}
+
+/// The ID of the person who made the request, as verified by `JwtMiddleware`.
+/// Handlers taking this as an argument will respond `401` to unauthenticated requests.
+#[derive(Clone, Copy, Debug)]
+pub struct AuthenticatedPerson(pub i32);
+
+impl FromRequest for AuthenticatedPerson {
+    type Error = ActixError;
+    type Future = Ready<std::result::Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match req.extensions().get::<AuthenticatedPerson>() {
+            Some(person) => ok(*person),
+            None => err(Error::Unauthorized.into()),
+        }
+    }
+}
+
+/// Middleware validating `Authorization: Bearer` tokens.
+///
+/// Requests with a valid token have an `AuthenticatedPerson` attached, requests
+/// with an invalid token are rejected, and requests without a token pass through.
+pub struct JwtMiddleware {
+    secret: String,
+}
+
+impl JwtMiddleware {
+    pub fn new(secret: String) -> JwtMiddleware {
+        JwtMiddleware { secret }
+    }
+}
+
+impl<S, B> Transform<S> for JwtMiddleware
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = ActixError;
+    type InitError = ();
+    type Transform = JwtMiddlewareService<S>;
+    type Future = Ready<std::result::Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(JwtMiddlewareService {
+            service,
+            secret: self.secret.clone(),
+        })
+    }
+}
+
+pub struct JwtMiddlewareService<S> {
+    service: S,
+    secret: String,
+}
+
+impl<S, B> Service for JwtMiddlewareService<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = ActixError;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let header = match req.headers().get(AUTHORIZATION) {
+            Some(header) => header.to_str().ok().map(|h| h.to_string()),
+            None => return Box::pin(self.service.call(req)),
+        };
+
+        let claims = header
+            .as_ref()
+            .and_then(|h| parse_bearer(h))
+            .and_then(|token| verify_token(token, &self.secret).ok());
+
+        match claims {
+            Some(claims) => {
+                req.extensions_mut().insert(AuthenticatedPerson(claims.sub));
+                Box::pin(self.service.call(req))
+            }
+            None => Box::pin(err(Error::Unauthorized.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test secret";
+
+    fn make_person(password_hash: Option<String>) -> Person {
+        Person {
+            id: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            username: Some("marcus".into()),
+            password_hash,
+        }
+    }
+
+    #[test]
+    fn test_token_round_trip() {
+        let token = create_token(42, SECRET).unwrap();
+        let claims = verify_token(&token, SECRET).unwrap();
+
+        assert_eq!(42, claims.sub);
+        assert!(claims.exp > Utc::now().timestamp() as usize);
+    }
+
+    #[test]
+    fn test_token_wrong_secret() {
+        let token = create_token(42, SECRET).unwrap();
+
+        assert!(verify_token(&token, "some other secret").is_err());
+    }
+
+    #[test]
+    fn test_token_expired() {
+        let claims = Claims {
+            sub: 42,
+            exp: (Utc::now() - Duration::hours(1)).timestamp() as usize,
+        };
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(SECRET.as_bytes()),
+        )
+        .unwrap();
+
+        assert!(verify_token(&token, SECRET).is_err());
+    }
+
+    #[test]
+    fn test_parse_bearer() {
+        assert_eq!(Some("abc.def"), parse_bearer("Bearer abc.def"));
+        assert_eq!(Some("abc.def"), parse_bearer("bearer abc.def"));
+        assert_eq!(None, parse_bearer("Bearer "));
+        assert_eq!(None, parse_bearer("Basic abc.def"));
+        assert_eq!(None, parse_bearer("abc.def"));
+    }
+
+    #[test]
+    fn test_verify_password() {
+        let hash = bcrypt::hash("hunter2", 4).unwrap();
+        let person = make_person(Some(hash));
+
+        assert!(verify_password(&person, "hunter2").unwrap());
+        assert!(!verify_password(&person, "hunter3").unwrap());
+        assert!(!verify_password(&make_person(None), "hunter2").unwrap());
+    }
+}
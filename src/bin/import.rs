@@ -1,13 +1,19 @@
+use std::collections::BTreeSet;
 use std::env;
 use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::io::{self, BufRead, BufReader, BufWriter};
+use std::path::Path;
 
+use chrono::{Datelike, NaiveDate};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use dotenv::dotenv;
 
-use drink_list::import::{DateContext, Drink, DrinkSet, QuantityRange, RawEntry, VolumeContext};
+use drink_list::import::error::ImportError;
+use drink_list::import::{
+    detect_year_rollover, DateContext, Drink, DrinkSet, QuantityRange, RawEntry, VolumeContext,
+};
+use drink_list::import_config::ImportConfig;
 use drink_list::models::TimePeriod;
 use drink_list::{models, schema};
 
@@ -17,60 +23,441 @@ fn establish_connection() -> PgConnection {
     PgConnection::establish(&database_url).expect(&format!("Error connecting to {}!", database_url))
 }
 
-fn create_drink(conn: &PgConnection, drink: &Drink) -> models::Drink {
+/// The number of rows saved by each `INSERT`, unless changed with `--batch-size N`.
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// An entry which has been parsed, waiting to be saved once its drink has been.
+struct PendingEntry {
+    line_number: usize,
+    line: String,
+    drink_id: i32,
+    drink: Drink,
+    date: DateContext,
+    quantity: QuantityRange,
+    volume: Option<VolumeContext>,
+}
+
+impl PendingEntry {
+    fn to_new_entry(&self) -> models::NewEntry<'_> {
+        models::NewEntry {
+            person_id: 1,
+            drank_on: &self.date.date,
+            time_period: &self.date.time,
+            context: &self.date.context,
+            drink_id: self.drink_id,
+            min_quantity: &self.quantity.min,
+            max_quantity: &self.quantity.max,
+            volume: self.volume.as_ref().map(|v| v.volume),
+            volume_ml: self.volume.as_ref().map(|v| v.volume.to_ml()),
+        }
+    }
+}
+
+/// Find the drink saved by a previous import, or create it if there is none.
+fn upsert_drink(conn: &PgConnection, drink: &Drink) -> QueryResult<models::Drink> {
     use schema::drink;
 
-    let new_drink = models::NewDrink {
-        name: drink.name.as_str(),
+    let new_drink = models::NewDrink::from(drink);
 
-        min_abv: drink.abv.as_ref().map(|abv| abv.min),
-        max_abv: drink.abv.as_ref().map(|abv| abv.max),
+    // The drink's unique key can't detect duplicates without an ABV, because NULLs are
+    // never equal, so check for an existing drink before inserting.
+    let existing = drink::table
+        .filter(drink::name.eq(new_drink.name))
+        .filter(drink::min_abv.is_not_distinct_from(&new_drink.min_abv))
+        .filter(drink::max_abv.is_not_distinct_from(&new_drink.max_abv))
+        .first::<models::Drink>(conn)
+        .optional()?;
 
-        multiplier: drink.multiplier,
-    };
+    if let Some(existing) = existing {
+        println!("Skipping existing drink: {}", drink.canonical_key());
+        return Ok(existing);
+    }
 
     diesel::insert_into(drink::table)
         .values(&new_drink)
+        .on_conflict((drink::name, drink::min_abv, drink::max_abv))
+        .do_nothing()
         .get_result(conn)
-        .expect("Error saving new drink")
 }
 
-fn create_entry(
+/// Save a batch of new drinks in a single transaction,
+/// returning the saved records in the same order as `drinks`.
+fn save_drinks(
     conn: &PgConnection,
-    drink_id: i32,
-    date: &DateContext,
-    quantity: &QuantityRange,
-    volume: &Option<VolumeContext>,
-) -> models::PlainEntry {
+    drinks: &[Drink],
+    upsert: bool,
+) -> QueryResult<Vec<models::Drink>> {
+    use schema::drink;
+
+    conn.transaction(|| {
+        // Existing drinks must be looked up individually; see `upsert_drink`.
+        if upsert {
+            return drinks.iter().map(|d| upsert_drink(conn, d)).collect();
+        }
+
+        let new_drinks: Vec<_> = drinks.iter().map(models::NewDrink::from).collect();
+        diesel::insert_into(drink::table)
+            .values(&new_drinks)
+            .get_results(conn)
+    })
+}
+
+/// Save a batch of entries in a single transaction. With `upsert`, entries which
+/// already exist are skipped, and only the entries which were created are returned.
+fn save_entries(
+    conn: &PgConnection,
+    entries: &[PendingEntry],
+    upsert: bool,
+) -> QueryResult<Vec<models::PlainEntry>> {
     use schema::entry;
 
-    let new_entry = models::NewEntry {
-        person_id: 1,
-        drank_on: &date.date,
-        time_period: &date.time,
-        context: &date.context,
-        drink_id: drink_id,
-        min_quantity: &quantity.min,
-        max_quantity: &quantity.max,
-        volume: volume.clone().as_ref().map(|v| v.volume),
-        volume_ml: volume.clone().as_ref().map(|v| v.volume.to_ml()),
+    let new_entries: Vec<_> = entries.iter().map(PendingEntry::to_new_entry).collect();
+
+    conn.transaction(|| {
+        let insert = diesel::insert_into(entry::table).values(&new_entries);
+
+        if !upsert {
+            return insert.get_results(conn);
+        }
+
+        // Duplicates are identified by the `entry_person_drink_time_key` unique index.
+        insert
+            .on_conflict((
+                entry::person_id,
+                entry::drank_on,
+                entry::time_period,
+                entry::drink_id,
+            ))
+            .do_nothing()
+            .get_results(conn)
+    })
+}
+
+/// A line of input, numbered from 1, with the entry parsed from it,
+/// or the error which prevented the line from being read.
+type InputLine = (usize, io::Result<(String, Option<RawEntry>)>);
+
+/// Number every line of `reader`. Lines which fail to read are kept,
+/// so that the lines after them keep their numbers.
+fn numbered_lines<R: BufRead>(reader: R) -> impl Iterator<Item = (usize, io::Result<String>)> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+}
+
+/// Read entries from a CSV file, along with the raw line each was parsed from.
+///
+/// Each line holds one entry. The lines are split before parsing, because the CSV reader
+/// silently skips blank lines, which must still be counted.
+fn read_csv<R: BufRead>(reader: R) -> impl Iterator<Item = InputLine> {
+    numbered_lines(reader).map(|(line_number, line)| {
+        let line = line.map(|line| {
+            let entry = parse_csv_line(&line);
+            (line, entry)
+        });
+        (line_number, line)
+    })
+}
+
+/// Parse a single line of CSV as an entry.
+fn parse_csv_line(line: &str) -> Option<RawEntry> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        // Fields are trimmed by `RawEntry::from_csv_record`, which needs the original spacing
+        // of any date containing commas.
+        .from_reader(line.as_bytes())
+        .into_records()
+        .next()?
+        .ok()
+        .and_then(RawEntry::from_csv_record)
+}
+
+/// Read entries from newline-delimited JSON, along with the raw line each was parsed from.
+fn read_ndjson<R: BufRead>(reader: R) -> impl Iterator<Item = InputLine> {
+    numbered_lines(reader).map(|(line_number, line)| {
+        let line = line.map(|line| {
+            let entry = RawEntry::from_json_str(&line);
+            (line, entry)
+        });
+        (line_number, line)
+    })
+}
+
+/// Load the drinks known from a previous import, or an empty set if there is no cache yet.
+fn load_drink_set(path: &str) -> io::Result<DrinkSet> {
+    if !Path::new(path).exists() {
+        return Ok(DrinkSet::new());
+    }
+
+    serde_json::from_reader(BufReader::new(File::open(path)?))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Save the drinks known after this import, for the next import to reuse.
+fn save_drink_set(path: &str, drink_set: &DrinkSet) -> io::Result<()> {
+    serde_json::to_writer(BufWriter::new(File::create(path)?), drink_set)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// Save the new drinks, then the pending entries, in batches of `batch_size`.
+/// Entries which fail to save, or whose drink fails to save, are added to `errors`.
+///
+/// Returns the number of entries created.
+fn save_pending(
+    conn: &PgConnection,
+    new_drinks: &[Drink],
+    pending: &mut Vec<PendingEntry>,
+    drink_set: &mut DrinkSet,
+    errors: &mut Vec<ImportError>,
+    upsert: bool,
+    batch_size: usize,
+) -> usize {
+    // Save the new drinks first, so that every entry can refer to its drink's ID.
+    let mut drink_ids = Vec::with_capacity(new_drinks.len());
+    for batch in new_drinks.chunks(batch_size) {
+        match save_drinks(conn, batch, upsert) {
+            Ok(saved) => {
+                for (drink, db_drink) in batch.iter().zip(saved) {
+                    drink_ids.push(Ok(drink_set.insert(db_drink.id, drink.clone())));
+                }
+            }
+            Err(e) => {
+                println!("ERROR: Failed to save {} drinks: {}", batch.len(), e);
+                let reason = format!("Failed to save drink: {}", e);
+                drink_ids.extend(batch.iter().map(|_| Err(reason.clone())));
+            }
+        }
+    }
+
+    for entry in pending.iter_mut().filter(|entry| entry.drink_id < 0) {
+        match &drink_ids[(-entry.drink_id - 1) as usize] {
+            Ok(id) => entry.drink_id = *id,
+            Err(reason) => errors.push(ImportError::new(
+                entry.line_number,
+                entry.line.clone(),
+                reason.clone(),
+            )),
+        }
+    }
+
+    // Entries whose drink failed to save still have a placeholder ID, and can't be saved.
+    pending.retain(|entry| entry.drink_id >= 0);
+
+    let mut created_count = 0;
+    for batch in pending.chunks(batch_size) {
+        let saved = match save_entries(conn, batch, upsert) {
+            Ok(saved) => saved,
+            Err(e) => {
+                println!("ERROR: Failed to save {} entries: {}", batch.len(), e);
+                errors.extend(batch.iter().map(|entry| {
+                    ImportError::new(
+                        entry.line_number,
+                        entry.line.clone(),
+                        format!("Failed to save entry: {}", e),
+                    )
+                }));
+                continue;
+            }
+        };
+
+        created_count += saved.len();
+
+        // With `--upsert`, any entry which wasn't returned already existed.
+        let created: BTreeSet<_> = saved
+            .iter()
+            .map(|entry| (entry.drank_on, entry.time, entry.drink_id))
+            .collect();
+
+        for entry in batch {
+            let PendingEntry {
+                drink_id,
+                drink,
+                date,
+                quantity,
+                volume,
+                ..
+            } = entry;
+
+            if !created.contains(&(date.date, date.time, *drink_id)) {
+                println!(
+                    "Skipping existing entry: {} | {} | ({}) {}",
+                    date.date.format("%d %b %Y"),
+                    date.time,
+                    drink_id,
+                    drink.name
+                );
+                continue;
+            }
+
+            println!(
+                "{:11} | {:9} | {:10} | {:10} | ({:3}) {:40} | {:5} | {:10}",
+                date.date.format("%d %b %Y"),
+                date.time,
+                date.context.join(", "),
+                quantity,
+                drink_id,
+                drink.name,
+                drink
+                    .abv
+                    .as_ref()
+                    .map(|a| a.to_string())
+                    .unwrap_or_default(),
+                volume.as_ref().map(|v| v.to_string()).unwrap_or_default()
+            );
+        }
+    }
+
+    created_count
+}
+
+/// Parse every entry without saving anything, then print a summary of the results,
+/// to check a file for mistakes before importing it.
+fn dry_run<I: Iterator<Item = InputLine>>(lines: I, config: &ImportConfig) -> io::Result<()> {
+    let mut previous_date = DateContext {
+        date: chrono::NaiveDate::from_ymd(2018, 1, 1),
+        time: TimePeriod::Evening,
+        context: vec![],
     };
 
-    diesel::insert_into(entry::table)
-        .values(&new_entry)
-        .get_result(conn)
-        .expect("Error saving new entry")
+    let mut total = 0;
+    let mut parsed = 0;
+    let mut skipped = 0;
+    let mut errors: Vec<ImportError> = Vec::new();
+    let mut drink_names = BTreeSet::new();
+
+    for (line_number, line) in lines {
+        total += 1;
+
+        let (line, entry) = match line {
+            Ok(line) => line,
+            Err(e) => {
+                let reason = format!("Failed to read line: {}", e);
+                errors.push(ImportError::new(line_number, String::new(), reason));
+                continue;
+            }
+        };
+
+        if RawEntry::is_ignored_line(&line) {
+            skipped += 1;
+            continue;
+        }
+
+        let entry = match entry {
+            Some(e) => e,
+            None => {
+                errors.push(ImportError::new(line_number, line, "Failed to parse entry"));
+                continue;
+            }
+        };
+
+        let date = DateContext::from_entry(&entry, &previous_date);
+        previous_date = date.into_previous();
+
+        if let Err(e) = QuantityRange::from_entry(&entry) {
+            errors.push(ImportError::new(
+                line_number,
+                line,
+                format!("Invalid quantity: {}", e),
+            ));
+            continue;
+        }
+
+        let drink = match Drink::from_entry_with_config(&entry, config) {
+            Ok(drink) => drink,
+            Err(e) => {
+                errors.push(ImportError::new(
+                    line_number,
+                    line,
+                    format!("Invalid ABV: {}", e),
+                ));
+                continue;
+            }
+        };
+        VolumeContext::from_entry_with_context(&entry, &date.context);
+
+        drink_names.insert(drink.name);
+        parsed += 1;
+    }
+
+    println!("Lines:           {}", total);
+    println!("Parsed:          {}", parsed);
+    println!("Skipped:         {}", skipped);
+    println!("Distinct drinks: {}", drink_names.len());
+    for name in drink_names.iter() {
+        println!("  {}", name);
+    }
+
+    report_errors(&errors)
+}
+
+/// Print a summary of the lines which could not be imported, returning an error if there
+/// were any, so that the import exits with a failure status.
+fn report_errors(errors: &[ImportError]) -> io::Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    println!("Errors:          {}", errors.len());
+    for error in errors.iter() {
+        println!("  {}", error);
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "Import completed with errors",
+    ))
 }
 
 fn main() -> std::io::Result<()> {
     dotenv().ok();
 
-    let db_conn = establish_connection();
+    // Entries are read as CSV by default, or as NDJSON with `--format=ndjson`.
+    let format = env::args()
+        .find(|arg| arg.starts_with("--format="))
+        .map(|arg| arg.trim_start_matches("--format=").to_string())
+        .unwrap_or("csv".into());
+
+    // Keywords used to detect doubles, etc, can be customized with a JSON config file.
+    let config = match env::args().find(|arg| arg.starts_with("--config=")) {
+        Some(arg) => ImportConfig::from_file(arg.trim_start_matches("--config="))?,
+        None => ImportConfig::default(),
+    };
+
+    // The entries are read from `--file <path>`, or stdin if the path is `-`. Otherwise,
+    // CSV is read from `drinks.csv`, and NDJSON from stdin.
+    let path = env::args()
+        .skip_while(|arg| arg != "--file")
+        .nth(1)
+        .unwrap_or_else(|| match format.as_str() {
+            "ndjson" => "-".into(),
+            _ => "drinks.csv".into(),
+        });
 
-    let f = File::open("drinks.csv")?;
-    let mut reader = BufReader::new(f);
+    let stdin = io::stdin();
+    let input: Box<dyn BufRead + '_> = match path.as_str() {
+        "-" => Box::new(stdin.lock()),
+        path => Box::new(BufReader::new(File::open(path)?)),
+    };
 
-    let mut line = String::new();
+    let lines: Box<dyn Iterator<Item = InputLine> + '_> = match format.as_str() {
+        "csv" => Box::new(read_csv(input)),
+        "ndjson" => Box::new(read_ndjson(input)),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unrecognized format, '{}'!", format),
+            ));
+        }
+    };
+
+    // With `--dry-run`, the entries are only parsed, and the database is never touched.
+    if env::args().any(|arg| arg == "--dry-run") {
+        return dry_run(lines, &config);
+    }
+
+    let db_conn = establish_connection();
 
     let mut previous_date = DateContext {
         date: chrono::NaiveDate::from_ymd(2018, 1, 1),
@@ -78,51 +465,214 @@ fn main() -> std::io::Result<()> {
         context: vec![],
     };
 
-    let mut drink_set = DrinkSet::new();
+    // Drinks created by previous imports can be cached in a JSON file, ex:
+    // `--cache-file drinks-cache.json`. The cache must be deleted if the database is reset.
+    let cache_file = env::args().skip_while(|arg| arg != "--cache-file").nth(1);
+
+    // With `--upsert`, drinks and entries which already exist are skipped rather than
+    // created again, so the same file can safely be imported more than once.
+    let upsert = env::args().any(|arg| arg == "--upsert");
 
-    while reader.read_line(&mut line)? > 0 {
-        let entry = RawEntry::from_line(&line.trim());
+    let batch_size = match env::args().skip_while(|arg| arg != "--batch-size").nth(1) {
+        Some(size) => match size.parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid batch size, '{}'!", size),
+                ));
+            }
+        },
+        None => DEFAULT_BATCH_SIZE,
+    };
+
+    let mut drink_set = match &cache_file {
+        Some(path) => load_drink_set(path)?,
+        None => DrinkSet::new(),
+    };
+
+    // The date of each imported line, to check the inferred years afterwards.
+    let mut imported: Vec<(String, NaiveDate)> = Vec::new();
+
+    // Lines which could not be imported are reported once every other line is done.
+    let mut errors: Vec<ImportError> = Vec::new();
+    let mut skipped = 0;
+
+    // Drinks and entries are saved in batches once every line has been parsed. Until then,
+    // new drinks are given negative placeholder IDs, ex: -1 for the first new drink.
+    let mut new_drinks: Vec<Drink> = Vec::new();
+    let mut pending_drinks = DrinkSet::new();
+    let mut pending: Vec<PendingEntry> = Vec::new();
+
+    for (line_number, line) in lines {
+        let (line, entry) = match line {
+            Ok(line) => line,
+            Err(e) => {
+                println!("ERROR: Failed to read line {}: {}", line_number, e);
+                let reason = format!("Failed to read line: {}", e);
+                errors.push(ImportError::new(line_number, String::new(), reason));
+                continue;
+            }
+        };
+
+        // Comments and blank lines are counted, but aren't errors.
+        if RawEntry::is_ignored_line(&line) {
+            skipped += 1;
+            continue;
+        }
 
         let entry = match entry {
             Some(e) => e,
             None => {
                 println!("ERROR: Failed to parse '{}'", line);
-                line.clear();
+                errors.push(ImportError::new(line_number, line, "Failed to parse entry"));
                 continue;
             }
         };
 
         let date = DateContext::from_entry(&entry, &previous_date);
-        previous_date = date.clone();
+        previous_date = date.into_previous();
 
-        let drink = Drink::from_entry(&entry);
-        let quantity = QuantityRange::from_entry(&entry);
-        let volume = VolumeContext::from_entry(&entry);
+        let mut quantity = match QuantityRange::from_entry(&entry) {
+            Ok(quantity) => quantity,
+            Err(e) => {
+                println!("ERROR: Invalid quantity in '{}': {}", line, e);
+                errors.push(ImportError::new(
+                    line_number,
+                    line,
+                    format!("Invalid quantity: {}", e),
+                ));
+                continue;
+            }
+        };
 
-        let id = match drink_set.find(&drink) {
-            Some(id) => id,
-            None => {
-                let db_drink = create_drink(&db_conn, &drink);
-                drink_set.insert(db_drink.id, drink.clone())
+        let drink = match Drink::from_entry_with_config(&entry, &config) {
+            Ok(drink) => drink,
+            Err(e) => {
+                println!("ERROR: Invalid ABV in '{}': {}", line, e);
+                errors.push(ImportError::new(
+                    line_number,
+                    line,
+                    format!("Invalid ABV: {}", e),
+                ));
+                continue;
             }
         };
 
-        create_entry(&db_conn, id, &date, &quantity, &volume);
+        imported.push((line.clone(), date.date));
+
+        let volume = VolumeContext::from_entry_with_context(&entry, &date.context);
 
+        let found = drink_set
+            .find(&drink)
+            .or_else(|| pending_drinks.find(&drink));
+        let beverage = drink_set
+            .find_beverage(&drink)
+            .or_else(|| pending_drinks.find_beverage(&drink));
+
+        let id = match (found, beverage) {
+            (Some(id), _) => id,
+            // Reuse the record for the same drink poured differently (ex: a single instead
+            // of a double), scaling the quantity so the number of drinks stays the same.
+            (None, Some(id)) => {
+                let existing = drink_set
+                    .get(id)
+                    .or_else(|| pending_drinks.get(id))
+                    .expect("Missing drink!");
+                println!("Reusing existing drink: {}", existing.canonical_key());
+                let scale = drink.multiplier / existing.multiplier;
+                quantity.min.num *= scale;
+                quantity.max.num *= scale;
+                id
+            }
+            (None, None) => {
+                new_drinks.push(drink.clone());
+                pending_drinks.insert(-(new_drinks.len() as i32), drink.clone())
+            }
+        };
+
+        pending.push(PendingEntry {
+            line_number,
+            line,
+            drink_id: id,
+            drink,
+            date,
+            quantity,
+            volume,
+        });
+    }
+
+    // With `--transactional`, nothing is saved unless every line is imported successfully.
+    let transactional = env::args().any(|arg| arg == "--transactional");
+
+    let mut created_count = 0;
+    let mut rolled_back = false;
+    if transactional {
+        let result = db_conn.transaction(|| {
+            created_count = save_pending(
+                &db_conn,
+                &new_drinks,
+                &mut pending,
+                &mut drink_set,
+                &mut errors,
+                upsert,
+                batch_size,
+            );
+
+            match errors.is_empty() {
+                true => Ok(()),
+                false => Err(diesel::result::Error::RollbackTransaction),
+            }
+        });
+
+        rolled_back = result.is_err();
+        match result {
+            Ok(()) => println!("Inserted {} entries.", created_count),
+            Err(diesel::result::Error::RollbackTransaction) => println!(
+                "ROLLED BACK: {} lines failed, so nothing was imported; {} entries would have been inserted.",
+                errors.len(),
+                created_count
+            ),
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to commit the import: {}", e),
+                ));
+            }
+        }
+    } else {
+        created_count = save_pending(
+            &db_conn,
+            &new_drinks,
+            &mut pending,
+            &mut drink_set,
+            &mut errors,
+            upsert,
+            batch_size,
+        );
+        println!("Inserted {} entries.", created_count);
+    }
+
+    println!("Skipped {} comment or blank lines.", skipped);
+
+    let dates: Vec<NaiveDate> = imported.iter().map(|(_, date)| *date).collect();
+    let rollovers = detect_year_rollover(&dates);
+    for &index in rollovers.iter() {
+        let (line, date) = &imported[index];
+        println!("Year changed to {} at '{}'", date.year(), line);
+    }
+
+    if rollovers.len() > 2 {
         println!(
-            "{:11} | {:9} | {:10} | {:10} | ({:3}) {:40} | {:5} | {:10}",
-            date.date.format("%d %b %Y"),
-            date.time,
-            date.context.join(", "),
-            quantity.print(),
-            id,
-            drink.name,
-            drink.abv.map(|a| a.print()).unwrap_or("".into()),
-            volume.map(|v| v.print()).unwrap_or("".into())
+            "WARNING: The year changed {} times; check that each year was inferred correctly!",
+            rollovers.len()
         );
+    }
 
-        line.clear();
+    // The drinks saved by a rolled back import no longer exist, so mustn't be cached.
+    if let (Some(path), false) = (&cache_file, rolled_back) {
+        save_drink_set(path, &drink_set)?;
     }
 
-    Ok(())
+    report_errors(&errors)
 }
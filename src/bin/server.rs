@@ -9,6 +9,7 @@ extern crate log;
 #[macro_use]
 extern crate derive_more;
 
+use std::collections::HashMap;
 use std::convert::From;
 use std::str::FromStr;
 
@@ -16,7 +17,7 @@ use actix_cors::Cors;
 use actix_web::middleware::Logger;
 use actix_web::*;
 use actix_web::{App, HttpRequest, HttpServer, Responder};
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, Local, NaiveDate};
 use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
 use futures::future::Either;
@@ -26,13 +27,33 @@ use futures::Future;
 use regex::Regex;
 
 use drink_list::api::{ApiResponse, ResponseStatus};
+use drink_list::auth;
+use drink_list::auth::{AuthenticatedPerson, JwtMiddleware, JwtSecret};
 use drink_list::db;
 use drink_list::db::{
-    Connection, CreateDrink, CreateEntry, GetDrink, GetDrinks, GetEntry, Pool, UpdateEntry, DeleteEntry,
+    BulkCreateEntries, BulkEntry, Connection, CountDrinks, CreateDrink, CreateEntry, DeleteEntry,
+    DeleteUnusedDrinks, GetAllDays, GetAllDrinks, GetContextSuggestions, GetCoverage, GetDrink,
+    GetDrinks, GetDrinksCountByDate, GetDrinksWithoutVolume, GetEntriesMissingVolumeMl, GetEntry,
+    GetEntryAuditLog, GetMonthlyReport, GetOrCreateDrink, GetPersonByUsername, GetSimilarDrinks,
+    GetSummary, GetWeeklyReport, MergeDrinks, MonthlyRow, Pool, SearchDrinks, UpdateEntry,
+    WeeklyRow,
 };
-use drink_list::import::{Abv, QuantityRange, VolumeContext};
+use drink_list::error::Error;
+use drink_list::export;
+use drink_list::import::{parse_context_tags, Abv, QuantityRange, VolumeContext};
+use drink_list::import_config::ImportConfig;
+use drink_list::models;
 use drink_list::models::TimePeriod;
-use drink_list::reports::{DrinkAggregate, DrinkAggregator};
+use drink_list::reports::{
+    abv_profile, abv_timeline, aggregate_day, context_counts, context_heatmap, context_timeline,
+    drinking_sessions, dry_months, entry_frequency_histogram, entry_gaps, fill_daily_counts,
+    month_summaries, most_common_time_period, percentile_daily_drinks, streak_history, streak_info,
+    time_period_distribution, top_drinks, volume_breakdown, weekend_vs_weekday, yearly_summaries,
+    AbvTimelinePoint, CalendarMatrix, ContextCount, ContextHeatmapCell, ContextTimelinePoint,
+    DrinkAggregate, DrinkAggregator, DrinkBudget, DrinkFrequency, DrinkHistory, DrinkStandard,
+    DrinkingSession, DryMonth, EntryGap, FrequencyBucket, MonthSummary, PersonStats, StreakInfo,
+    StreakPeriod, VolumeBreakdown, WeeklyReport, WeightedUnits, YearlySummary,
+};
 
 type ActixResult<T> = std::result::Result<T, actix_web::error::Error>;
 
@@ -43,6 +64,19 @@ struct AggregatedEntry {
     pub aggregate: DrinkAggregate,
 }
 
+impl AggregatedEntry {
+    fn new(entry: db::Entry, standard: &DrinkStandard) -> AggregatedEntry {
+        AggregatedEntry {
+            aggregate: entry.aggregate_with_standard(standard),
+            entry: entry,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "drinks")]
+struct Drinks(Vec<AggregatedEntry>);
+
 async fn index() -> impl Responder {
     #[derive(Serialize)]
     #[serde(rename = "message")]
@@ -60,52 +94,1509 @@ async fn wakeup() -> impl Responder {
     HttpResponse::Ok().json(ApiResponse::success(TestResponse("👍".into())))
 }
 
-/// Route to get all drinks from all time.
-async fn get_entries(pool: web::Data<Pool>) -> ActixResult<HttpResponse> {
-    get_entries_internal(pool, None).await
+#[derive(Deserialize)]
+struct EntriesQuery {
+    pub drink_id: Option<i32>,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Route to get all drinks from all time, optionally only those of a specific drink,
+/// or only those drank from `start` through `end`, inclusive.
+///
+/// The entries can be paged through with `limit` and `offset`; the response's
+/// `total_count` is the number of entries across every page.
+async fn get_entries(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<EntriesQuery>,
+) -> ActixResult<HttpResponse> {
+    let date_range = match (query.start, query.end) {
+        (Some(start), Some(end)) if start > end => {
+            let response = ApiResponse::error_message("The start date must not be after the end!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+        (Some(start), Some(end)) => Some((start, end)),
+        (None, None) => None,
+        _ => {
+            let response = ApiResponse::error_message("Both a start and end date are required!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    if query.limit.map_or(false, |limit| limit < 1) {
+        let response = ApiResponse::error_message("Limit must be at least one!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    if query.offset.map_or(false, |offset| offset < 0) {
+        let response = ApiResponse::error_message("Offset must not be negative!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    get_entries_internal(
+        pool,
+        standard,
+        GetDrinks {
+            person_id: 1,
+            date_range,
+            drink_id: query.drink_id,
+            time_period: None,
+            context_contains: None,
+            limit: query.limit,
+            offset: query.offset,
+        },
+    )
+    .await
+}
+
+async fn get_entries_by_date(
+    (pool, standard, path): (
+        web::Data<Pool>,
+        web::Data<DrinkStandard>,
+        web::Path<NaiveDate>,
+    ),
+) -> ActixResult<HttpResponse> {
+    let date = path.into_inner();
+    get_entries_internal(
+        pool,
+        standard,
+        GetDrinks {
+            person_id: 1,
+            date_range: Some((date.clone(), date)),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await
+}
+
+/// Route to get the total of every entry drank on the given day.
+async fn get_day_summary(
+    (pool, standard, path): (
+        web::Data<Pool>,
+        web::Data<DrinkStandard>,
+        web::Path<NaiveDate>,
+    ),
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let date = path.into_inner();
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: Some((date, date)),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(aggregate_day(&entries, &standard)).into())
+}
+
+/// Route to get the entries from a single occasion, ex: the evening of a given day.
+async fn get_entries_by_occasion(
+    (pool, standard, path): (
+        web::Data<Pool>,
+        web::Data<DrinkStandard>,
+        web::Path<(NaiveDate, String)>,
+    ),
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let (date, time_period) = path.into_inner();
+    let time_period = match TimePeriod::from_str(&time_period.to_lowercase()) {
+        Some(time_period) => time_period,
+        None => {
+            let response = ApiResponse::error_message("Invalid time period!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    get_entries_internal(
+        pool,
+        standard,
+        GetDrinks {
+            person_id,
+            date_range: Some((date, date)),
+            drink_id: None,
+            time_period: Some(time_period),
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await
+}
+
+/// Internal route handler, to allow other routes to all share the same handler code.
+///
+/// The response's `total_count` is the number of entries matching `query`, ignoring its
+/// `limit` and `offset`.
+async fn get_entries_internal(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: GetDrinks,
+) -> ActixResult<HttpResponse> {
+    let total_count = db::execute(
+        &pool,
+        CountDrinks {
+            query: query.clone(),
+        },
+    )
+    .await?;
+    let entries = db::execute(&pool, query).await?;
+
+    let drinks = Drinks(
+        entries
+            .into_iter()
+            .map(|entry| AggregatedEntry::new(entry, &standard))
+            .collect(),
+    );
+
+    Ok(ApiResponse::success(drinks)
+        .with_total_count(total_count)
+        .into())
+}
+
+/// Route to get all entries which have no volume information.
+async fn get_entries_without_volume(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    db::execute(&pool, GetDrinksWithoutVolume { person_id })
+        .and_then(|drinks| {
+            async move {
+                let drinks = Drinks(
+                    drinks
+                        .into_iter()
+                        .map(|entry| AggregatedEntry::new(entry, &standard))
+                        .collect(),
+                );
+
+                Ok(HttpResponse::from(ApiResponse::success(drinks)))
+            }
+        })
+        .map_err(|e| actix_web::Error::from(e))
+        .await
+}
+
+/// Route to get all entries which have a volume, but no volume in mL.
+async fn get_entries_missing_volume_ml(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let entries = db::execute(&pool, GetEntriesMissingVolumeMl { person_id }).await?;
+    let drinks = Drinks(
+        entries
+            .into_iter()
+            .map(|entry| AggregatedEntry::new(entry, &standard))
+            .collect(),
+    );
+
+    Ok(ApiResponse::success(drinks).into())
+}
+
+/// Route to recalculate the volume in mL of every entry which is missing it.
+async fn fix_entries_volume_ml(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let entries = db::execute(&pool, GetEntriesMissingVolumeMl { person_id }).await?;
+    let count = entries.len();
+
+    for entry in entries {
+        // Updating the volume will recalculate the volume in mL.
+        let volume = entry.volume.map(|volume| VolumeContext {
+            volume,
+            original_unit: None,
+        });
+
+        db::execute(
+            &pool,
+            UpdateEntry {
+                entry,
+                person_id,
+                volume: Some(volume),
+            },
+        )
+        .await?;
+    }
+
+    Ok(ApiResponse::success_message(format!("Fixed {} entries", count)).into())
+}
+
+/// Route to get counts of how many entries have ABV and volume information.
+async fn get_coverage(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    db::execute(&pool, GetCoverage { person_id })
+        .and_then(|coverage| async move { Ok(HttpResponse::from(ApiResponse::success(coverage))) })
+        .map_err(|e| actix_web::Error::from(e))
+        .await
+}
+
+/// A drink definition, along with the label to display for it.
+#[derive(Serialize)]
+struct DrinkDefinition {
+    #[serde(flatten)]
+    pub drink: models::Drink,
+    pub display_name: String,
+}
+
+#[derive(Deserialize)]
+struct DefinitionsQuery {
+    pub has_entries: Option<bool>,
+}
+
+/// Route to get all drink definitions, optionally only those with or without entries.
+async fn get_drink_definitions(
+    pool: web::Data<Pool>,
+    query: web::Query<DefinitionsQuery>,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "definitions")]
+    struct Definitions(Vec<DrinkDefinition>);
+
+    let has_entries = query.has_entries;
+    db::execute(&pool, GetAllDrinks { has_entries })
+        .and_then(|drinks| {
+            async move {
+                let definitions = Definitions(
+                    drinks
+                        .into_iter()
+                        .map(|drink| DrinkDefinition {
+                            display_name: drink.display_name(),
+                            drink: drink,
+                        })
+                        .collect(),
+                );
+
+                Ok(HttpResponse::from(ApiResponse::success(definitions)))
+            }
+        })
+        .map_err(|e| actix_web::Error::from(e))
+        .await
+}
+
+#[derive(Deserialize)]
+struct BudgetQuery {
+    pub weekly_limit: Option<f32>,
+    pub system: Option<String>,
+}
+
+/// Route to get this week's progress towards a weekly drinking limit.
+async fn get_budget(
+    pool: web::Data<Pool>,
+    query: web::Query<BudgetQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    // Default to the UK guideline of 14 units per week.
+    let weekly_limit = query.weekly_limit.unwrap_or(14.0);
+    if weekly_limit <= 0.0 {
+        let response = ApiResponse::error_message("Weekly limit must be greater than zero!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let standard = match DrinkStandard::from_system(query.system.as_deref().unwrap_or("uk")) {
+        Some(standard) => standard,
+        None => {
+            let response = ApiResponse::error_message("Unrecognized unit system!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    // Find the Monday and Sunday of the current ISO week.
+    let today = Local::today().naive_local();
+    let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let week_end = week_start + Duration::days(6);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: Some((week_start, week_end)),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let budget = DrinkBudget::from_entries(&entries, weekly_limit, &standard);
+
+    Ok(ApiResponse::success(budget).into())
+}
+
+#[derive(Deserialize)]
+struct CountByDateQuery {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// Route to get how many entries were drunk on each day within a date range,
+/// including days without any entries.
+async fn get_count_by_date(
+    pool: web::Data<Pool>,
+    query: web::Query<CountByDateQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "counts")]
+    struct Counts(HashMap<NaiveDate, u32>);
+
+    let counts = db::execute(
+        &pool,
+        GetDrinksCountByDate {
+            person_id,
+            start: query.start,
+            end: query.end,
+        },
+    )
+    .await?;
+
+    let days = fill_daily_counts(&counts, query.start, query.end);
+
+    Ok(ApiResponse::success(Counts(days)).into())
+}
+
+#[derive(Deserialize)]
+struct WeightedUnitsQuery {
+    pub system: Option<String>,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+/// Route to get the total number of standard drinks, counting only entries
+/// with both ABV and volume information.
+async fn get_weighted_units(
+    pool: web::Data<Pool>,
+    query: web::Query<WeightedUnitsQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let date_range = match (query.start, query.end) {
+        (Some(start), Some(end)) => Some((start, end)),
+        (None, None) => None,
+        _ => {
+            let response = ApiResponse::error_message("Both a start and end date are required!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    match WeightedUnits::from_entries(&entries, query.system.as_deref().unwrap_or("uk")) {
+        Some(units) => Ok(ApiResponse::success(units).into()),
+        None => {
+            let response = ApiResponse::error_message("Unrecognized unit system!");
+            Ok(HttpResponse::BadRequest().json(response))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PercentileQuery {
+    pub p: i64,
+}
+
+/// Route to get the `p`th percentile of the number of drinks per day.
+async fn get_percentile(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<PercentileQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "percentile")]
+    struct Percentile {
+        percentile: u8,
+        daily_drinks: f32,
+    }
+
+    if query.p < 0 || query.p > 100 {
+        let response = ApiResponse::error_message("Percentile must be between 0 and 100!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let percentile = query.p as u8;
+    let result = Percentile {
+        percentile,
+        daily_drinks: percentile_daily_drinks(&entries, percentile, &standard),
+    };
+
+    Ok(ApiResponse::success(result).into())
+}
+
+#[derive(Deserialize)]
+struct EntryGapsQuery {
+    pub threshold_days: Option<i64>,
+}
+
+/// Route to get every gap between entries which is longer than `threshold_days`.
+async fn get_entry_gaps(
+    pool: web::Data<Pool>,
+    query: web::Query<EntryGapsQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "gaps")]
+    struct Gaps(Vec<EntryGap>);
+
+    let threshold_days = query.threshold_days.unwrap_or(7);
+    if threshold_days < 1 {
+        let response = ApiResponse::error_message("Threshold must be at least one day!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let days = db::execute(&pool, GetAllDays { person_id }).await?;
+    let gaps = Gaps(entry_gaps(&days, threshold_days as u32));
+
+    Ok(ApiResponse::success(gaps).into())
+}
+
+#[derive(Deserialize)]
+struct FrequencyHistogramQuery {
+    pub bucket_days: Option<i64>,
+}
+
+/// Route to get a histogram of how many days pass between drinking days.
+async fn get_frequency_histogram(
+    pool: web::Data<Pool>,
+    query: web::Query<FrequencyHistogramQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "histogram")]
+    struct Histogram(Vec<FrequencyBucket>);
+
+    let bucket_days = query.bucket_days.unwrap_or(1);
+    if bucket_days < 1 {
+        let response = ApiResponse::error_message("Bucket size must be at least one day!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let histogram = Histogram(entry_frequency_histogram(&entries, bucket_days as u32));
+
+    Ok(ApiResponse::success(histogram).into())
+}
+
+/// Route to get the alternating periods of drinking and sober days, up to today.
+async fn get_streak_history(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "streaks")]
+    struct Streaks(Vec<StreakPeriod>);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let today = Local::today().naive_local();
+
+    Ok(ApiResponse::success(Streaks(streak_history(&entries, today))).into())
+}
+
+/// Route to get totals for each year in which anything was drunk.
+async fn get_yearly_summary(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "years")]
+    struct Years(Vec<YearlySummary>);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Years(yearly_summaries(&entries, &standard))).into())
+}
+
+#[derive(Deserialize)]
+struct DryMonthsQuery {
+    pub start_year: i32,
+    pub end_year: i32,
+}
+
+/// Route to get every entry of a single drink in chronological order, with running totals.
+async fn get_drink_history(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    path: web::Path<i32>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: Some(path.into_inner()),
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    match DrinkHistory::from_entries(entries, &standard) {
+        Some(history) => Ok(ApiResponse::success(history).into()),
+        None => {
+            let response = ApiResponse::error_message("Not found");
+            Ok(HttpResponse::NotFound().json(response))
+        }
+    }
+}
+
+/// A drinking session, with the aggregate of each of its entries.
+#[derive(Serialize)]
+struct SessionResponse {
+    pub date: NaiveDate,
+    pub time_period: TimePeriod,
+    pub context: Vec<String>,
+    pub entries: Vec<AggregatedEntry>,
+    pub total: DrinkAggregate,
+}
+
+impl SessionResponse {
+    fn new(session: DrinkingSession, standard: &DrinkStandard) -> SessionResponse {
+        SessionResponse {
+            date: session.date,
+            time_period: session.time_period,
+            context: session.context,
+            entries: session
+                .entries
+                .into_iter()
+                .map(|entry| AggregatedEntry::new(entry, standard))
+                .collect(),
+            total: session.total,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SessionsQuery {
+    pub limit: Option<usize>,
+}
+
+/// Route to get the most recent drinking sessions, grouping entries by day and time period.
+async fn get_sessions(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<SessionsQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "sessions")]
+    struct Sessions(Vec<SessionResponse>);
+
+    let limit = query.limit.unwrap_or(20);
+    if limit < 1 {
+        let response = ApiResponse::error_message("Limit must be at least one!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let sessions = drinking_sessions(entries, &standard)
+        .into_iter()
+        .take(limit)
+        .map(|session| SessionResponse::new(session, &standard))
+        .collect();
+
+    Ok(ApiResponse::success(Sessions(sessions)).into())
+}
+
+/// Route to get a summary of each month of the given year.
+async fn get_year_summary(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    path: web::Path<String>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "months")]
+    struct Months(Vec<MonthSummary>);
+
+    let year = match path.parse::<i32>() {
+        Ok(year) if path.len() == 4 && year >= 2000 && year <= 2100 => year,
+        _ => {
+            let response = ApiResponse::error_message("Year must be between 2000 and 2100!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: Some((
+                NaiveDate::from_ymd(year, 1, 1),
+                NaiveDate::from_ymd(year, 12, 31),
+            )),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Months(month_summaries(&entries, year, &standard))).into())
+}
+
+#[derive(Deserialize)]
+struct CalendarMatrixQuery {
+    pub year: i32,
+    pub month: u32,
+}
+
+/// Route to get a month of entries, laid out as calendar weeks.
+async fn get_calendar_matrix(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<CalendarMatrixQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let (year, month) = (query.year, query.month);
+    let first = match NaiveDate::from_ymd_opt(year, month, 1) {
+        Some(first) => first,
+        None => {
+            let response = ApiResponse::error_message("Invalid year or month!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    // Every month spans fewer than 32 days, so this lands within the next month.
+    let next_month = first + Duration::days(32);
+    let last = next_month - Duration::days(next_month.day() as i64);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: Some((first, last)),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(CalendarMatrix::for_month(year, month, &entries, &standard)).into())
+}
+
+/// Route to get every calendar month between `start_year` and `end_year` with no entries.
+async fn get_dry_months(
+    pool: web::Data<Pool>,
+    query: web::Query<DryMonthsQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "dry_months")]
+    struct DryMonths(Vec<DryMonth>);
+
+    if query.start_year > query.end_year {
+        let response = ApiResponse::error_message("Start year must not be after the end year!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    if query.end_year - query.start_year >= 10 {
+        let response = ApiResponse::error_message("Range must span at most ten years!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: Some((
+                NaiveDate::from_ymd(query.start_year, 1, 1),
+                NaiveDate::from_ymd(query.end_year, 12, 31),
+            )),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let months = dry_months(&entries, query.start_year, query.end_year)
+        .into_iter()
+        .map(DryMonth::from)
+        .collect();
+
+    Ok(ApiResponse::success(DryMonths(months)).into())
+}
+
+/// Route to get how many entries were drunk during each time of day.
+async fn get_time_distribution(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "time_distribution")]
+    struct TimeDistribution(Vec<(TimePeriod, u32)>);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let distribution = TimeDistribution(time_period_distribution(&entries));
+
+    Ok(ApiResponse::success(distribution).into())
+}
+
+/// Route to get how many entries had each context tag on each day of the week.
+async fn get_context_heatmap(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "heatmap")]
+    struct Heatmap(Vec<ContextHeatmapCell>);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Heatmap(context_heatmap(&entries))).into())
+}
+
+#[derive(Deserialize)]
+struct ContextBreakdownQuery {
+    pub min_count: Option<u32>,
+}
+
+/// Route to get how many entries were tagged with each context, most common first.
+async fn get_context_breakdown(
+    pool: web::Data<Pool>,
+    query: web::Query<ContextBreakdownQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "contexts")]
+    struct Contexts(Vec<ContextCount>);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let counts = context_counts(&entries, query.min_count.unwrap_or(0));
+
+    Ok(ApiResponse::success(Contexts(counts)).into())
+}
+
+#[derive(Deserialize)]
+struct WeekendQuery {
+    pub since: Option<NaiveDate>,
+}
+
+/// Route to compare the average drinks per drinking day on weekdays and weekends.
+async fn get_weekend_vs_weekday(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<WeekendQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let today = Local::today().naive_local();
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: query.since.map(|since| (since, today)),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(weekend_vs_weekday(&entries, &standard)).into())
+}
+
+#[derive(Deserialize)]
+struct ContextSuggestionsQuery {
+    pub prefix: String,
+    pub limit: Option<u32>,
+}
+
+/// Route to suggest previously used context tags which begin with the given prefix.
+async fn get_context_suggestions(
+    pool: web::Data<Pool>,
+    query: web::Query<ContextSuggestionsQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "suggestions")]
+    struct Suggestions(Vec<String>);
+
+    let prefix = query.prefix.trim();
+    if prefix.is_empty() {
+        let response = ApiResponse::error_message("Prefix must be at least one character!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let limit = query.limit.unwrap_or(10);
+    if limit > 50 {
+        let response = ApiResponse::error_message("Limit must be at most 50!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let suggestions = db::execute(
+        &pool,
+        GetContextSuggestions {
+            person_id,
+            prefix: prefix.to_string(),
+            limit,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Suggestions(suggestions)).into())
+}
+
+#[derive(Deserialize)]
+struct ContextTimelineQuery {
+    pub context: String,
+    pub since: Option<NaiveDate>,
+}
+
+/// Route to get a monthly timeline of the entries tagged with a given context.
+async fn get_context_timeline(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<ContextTimelineQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "timeline")]
+    struct Timeline(Vec<ContextTimelinePoint>);
+
+    let context = query.context.trim();
+    if context.is_empty() || context.chars().count() > 100 {
+        let response = ApiResponse::error_message("Context must be between 1 and 100 characters!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let today = Local::today().naive_local();
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: query.since.map(|since| (since, today)),
+            drink_id: None,
+            time_period: None,
+            context_contains: Some(context.to_string()),
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Timeline(context_timeline(&entries, &standard))).into())
+}
+
+#[derive(Deserialize)]
+struct MergeForm {
+    pub source_id: i32,
+    pub target_id: i32,
+}
+
+/// Route to merge two records of the same drink, moving every entry of the source
+/// drink to the target, and then deleting the source.
+async fn merge_drinks(
+    pool: web::Data<Pool>,
+    form: web::Json<MergeForm>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    if form.source_id == form.target_id {
+        let response = ApiResponse::error_message("Can not merge a drink into itself!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let merge = db::execute(
+        &pool,
+        MergeDrinks {
+            person_id,
+            source_id: form.source_id,
+            target_id: form.target_id,
+        },
+    )
+    .await?;
+
+    match merge {
+        Some(merge) => Ok(ApiResponse::success(merge).into()),
+        None => {
+            let response = ApiResponse::error_message("Not found");
+            Ok(HttpResponse::NotFound().json(response))
+        }
+    }
+}
+
+/// Route to delete every drink definition which has no entries.
+///
+/// Drinks are shared by everyone, so any authenticated person may clean them up.
+async fn delete_unused_drinks(
+    pool: web::Data<Pool>,
+    _person: AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let deleted = db::execute(&pool, DeleteUnusedDrinks).await?;
+
+    Ok(ApiResponse::success_message(format!("Deleted {} drinks", deleted)).into())
+}
+
+#[derive(Deserialize)]
+struct SimilarQuery {
+    pub drink_id: i32,
+    pub prefix_len: Option<usize>,
+}
+
+/// Route to get drinks which may be the same as the given drink,
+/// based on their names and ABV.
+async fn get_similar_drinks(
+    pool: web::Data<Pool>,
+    query: web::Query<SimilarQuery>,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "drinks")]
+    struct Similar(Vec<models::Drink>);
+
+    let drinks = db::execute(
+        &pool,
+        GetSimilarDrinks {
+            drink_id: query.drink_id,
+            name_prefix_len: query.prefix_len.unwrap_or(5),
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Similar(drinks)).into())
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    pub q: Option<String>,
+}
+
+/// Route to find up to 20 drinks whose names contain the query `q`.
+async fn search_drinks(
+    pool: web::Data<Pool>,
+    query: web::Query<SearchQuery>,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "drinks")]
+    struct Found(Vec<models::Drink>);
+
+    let search = match query.into_inner().q {
+        Some(q) if !q.trim().is_empty() => q.trim().to_string(),
+        _ => {
+            let response = ApiResponse::error_message("A search query is required!");
+            return Ok(HttpResponse::BadRequest().json(response));
+        }
+    };
+
+    let drinks = db::execute(&pool, SearchDrinks { query: search }).await?;
+
+    Ok(ApiResponse::success(Found(drinks)).into())
+}
+
+/// Route to get the total volume of entries recorded in each unit.
+async fn get_volume_breakdown(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "volumes")]
+    struct Volumes(Vec<VolumeBreakdown>);
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Volumes(volume_breakdown(&entries))).into())
+}
+
+/// Route to get a summary of the ABV of everything a person has drunk.
+async fn get_abv_profile(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(abv_profile(&entries)).into())
+}
+
+#[derive(Deserialize)]
+struct AbvTimelineQuery {
+    /// Either "month" (the default) or "week".
+    pub granularity: Option<String>,
+}
+
+/// Route to get the average ABV of the drinks drunk in each month or week.
+async fn get_abv_timeline(
+    pool: web::Data<Pool>,
+    query: web::Query<AbvTimelineQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "timeline")]
+    struct Timeline(Vec<AbvTimelinePoint>);
+
+    let granularity = query.granularity.as_deref().unwrap_or("month");
+    if granularity != "month" && granularity != "week" {
+        let response = ApiResponse::error_message("Granularity must be 'month' or 'week'!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Timeline(abv_timeline(&entries, granularity))).into())
+}
+
+/// Route to get the statistics shown on the dashboard, all at once.
+async fn get_summary(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "summary")]
+    struct DashboardSummary {
+        all_time: PersonStats,
+        this_week: WeeklyReport,
+        this_month: MonthSummary,
+        streaks: StreakInfo,
+        top_drinks: Vec<DrinkFrequency>,
+        recent_entries: Vec<AggregatedEntry>,
+    }
+
+    // Every statistic is calculated from the same set of entries, loaded by a single query.
+    let mut entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let today = Local::today().naive_local();
+    let this_month = month_summaries(&entries, today.year(), &standard)
+        .into_iter()
+        .nth(today.month0() as usize)
+        .expect("Missing month summary!");
+
+    let mut summary = DashboardSummary {
+        all_time: PersonStats::from_entries(&entries, &standard),
+        this_week: WeeklyReport::from_entries(&entries, today, &standard),
+        this_month,
+        streaks: streak_info(&entries, today),
+        top_drinks: top_drinks(&entries, 5),
+        recent_entries: Vec::new(),
+    };
+
+    entries.sort_by_key(|entry| std::cmp::Reverse((entry.drank_on, entry.time, entry.id)));
+    summary.recent_entries = entries
+        .into_iter()
+        .take(10)
+        .map(|entry| AggregatedEntry::new(entry, &standard))
+        .collect();
+
+    Ok(HttpResponse::Ok()
+        .header("Cache-Control", "private, max-age=60")
+        .json(ApiResponse::success(summary)))
+}
+
+/// Route to get all-time totals of a person's entries, calculated by the database.
+async fn get_all_time_summary(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let summary = db::execute(
+        &pool,
+        GetSummary {
+            person_id,
+            standard: *standard.get_ref(),
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(summary).into())
+}
+
+#[derive(Deserialize)]
+struct WeeklyReportQuery {
+    pub year: Option<i32>,
+}
+
+/// Route to get the total number of drinks of each week, optionally only within a `year`.
+async fn get_weekly_report(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    query: web::Query<WeeklyReportQuery>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "weeks")]
+    struct Weeks(Vec<WeeklyRow>);
+
+    let weeks = db::execute(
+        &pool,
+        GetWeeklyReport {
+            person_id,
+            year: query.year,
+            standard: *standard.get_ref(),
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Weeks(weeks)).into())
+}
+
+/// Route to get the total number of drinks of each month.
+async fn get_monthly_report(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "months")]
+    struct Months(Vec<MonthlyRow>);
+
+    let months = db::execute(
+        &pool,
+        GetMonthlyReport {
+            person_id,
+            standard: *standard.get_ref(),
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(Months(months)).into())
+}
+
+/// Route to get a summary of a person's drinking patterns.
+async fn get_stats(
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "stats")]
+    struct Stats {
+        most_common_time_period: Option<TimePeriod>,
+        time_period_distribution: Vec<(TimePeriod, u32)>,
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let stats = Stats {
+        most_common_time_period: most_common_time_period(&entries),
+        time_period_distribution: time_period_distribution(&entries),
+    };
+
+    Ok(ApiResponse::success(stats).into())
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    pub format: Option<String>,
+}
+
+/// Route to export a person's full drink history, as either a CSV file,
+/// or a ZIP archive containing one CSV file per year.
+///
+/// People may only export their own history.
+async fn export_entries(
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    query: web::Query<ExportQuery>,
+    person: AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let person_id = path.into_inner();
+    if person_id != person.0 {
+        let response = ApiResponse::error_message("Not allowed to export this person's drinks!");
+        return Ok(HttpResponse::Forbidden().json(response));
+    }
+
+    let format = query.format.as_deref().unwrap_or("csv");
+    if format != "csv" && format != "zip" {
+        let response = ApiResponse::error_message("Export format must be either 'csv' or 'zip'!");
+        return Ok(HttpResponse::BadRequest().json(response));
+    }
+
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    // There is no point in splitting up a single year of data.
+    if format == "zip" && export::group_by_year(&entries).len() > 1 {
+        let zip = export::write_zip(&entries)?;
+
+        return Ok(HttpResponse::Ok()
+            .content_type("application/zip")
+            .header(
+                "Content-Disposition",
+                "attachment; filename=\"drinks-export.zip\"",
+            )
+            .body(zip));
+    }
+
+    let mut csv = Vec::new();
+    export::write_csv(&entries, &mut csv)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .header(
+            "Content-Disposition",
+            "attachment; filename=\"drinks-export.csv\"",
+        )
+        .body(csv))
 }
 
-async fn get_entries_by_date(
-    (pool, path): (web::Data<Pool>, web::Path<NaiveDate>),
+/// Route to export every drinking session as an iCalendar feed.
+async fn export_sessions_ical(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
 ) -> ActixResult<HttpResponse> {
-    let date = path.into_inner();
-    get_entries_internal(pool, Some((date.clone(), date))).await
+    let entries = db::execute(
+        &pool,
+        GetDrinks {
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let ical = export::write_ical(&drinking_sessions(entries, &standard), chrono::Utc::now());
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .header("Content-Disposition", "attachment; filename=\"drinks.ics\"")
+        .body(ical))
 }
 
-/// Internal route handler, to allow other routes to all share the same handler code.
-///
-async fn get_entries_internal(
+/// Route to export every entry as newline-delimited JSON, one aggregated entry per line.
+async fn export_entries_json(
     pool: web::Data<Pool>,
-    date_range: Option<(NaiveDate, NaiveDate)>,
+    standard: web::Data<DrinkStandard>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
 ) -> ActixResult<HttpResponse> {
-    #[derive(Serialize)]
-    #[serde(rename = "drinks")]
-    struct Drinks(Vec<AggregatedEntry>);
-
-    db::execute(
+    let entries = db::execute(
         &pool,
         GetDrinks {
-            person_id: 1,
-            date_range: date_range,
-        },
-    )
-    .and_then(|drinks| {
-        async move {
-            let drinks = Drinks(
-                drinks
-                    .into_iter()
-                    .map(|entry| AggregatedEntry {
-                        aggregate: entry.aggregate(),
-                        entry: entry,
-                    })
-                    .collect(),
-            );
-
-            Ok(HttpResponse::from(ApiResponse::success(drinks)))
-        }
-    })
-    .map_err(|e| actix_web::Error::from(e))
-    .await
+            person_id,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        },
+    )
+    .await?;
+
+    let entries: Vec<AggregatedEntry> = entries
+        .into_iter()
+        .map(|entry| AggregatedEntry::new(entry, &standard))
+        .collect();
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(export::JsonExportStream::new(entries)))
 }
 
 #[derive(Deserialize)]
@@ -121,79 +1612,135 @@ struct EntryForm {
     pub abv: Option<String>,
 
     pub volume: Option<String>,
+
+    /// Comma separated context tags, ex: "pub, birthday".
+    pub context: Option<String>,
 }
 
-fn new_entry(
-    pool: web::Data<Pool>,
-    form: web::Form<EntryForm>,
-) -> impl Future<Output = Result<HttpResponse>> {
-    use futures::future;
+/// The parsed and validated values of an `EntryForm`.
+struct ParsedEntry {
+    pub time_period: TimePeriod,
+    pub context: Vec<String>,
+    pub quantity: QuantityRange,
+    pub abv: Option<Abv>,
+    pub volume: Option<VolumeContext>,
+    pub name: String,
+    pub multiplier: f32,
+}
 
-    let time_period = match TimePeriod::from_str(&form.time_period.to_lowercase()) {
-        Some(time_period) => time_period,
-        None => {
-            info!(
-                "Received invalid time period input, '{}'!",
-                form.time_period
-            );
-            let response = ApiResponse::error_message("Invalid time period value!");
-            return Either::Left(future::ok(HttpResponse::BadRequest().json(response)));
-        }
-    };
-    // Attempt to parse the quantity string.
-    let quantity = match QuantityRange::from_str(&form.quantity) {
-        Ok(quantity) => quantity,
-        Err(e) => {
-            info!("Received invalid quantity input, '{}'!", form.quantity);
-            let response = ApiResponse::error_message("Invalid quantity value!");
-            return Either::Left(future::ok(HttpResponse::BadRequest().json(response)));
-        }
-    };
+impl EntryForm {
+    /// Parse the form's input values, returning an error if any are invalid.
+    fn parse(&self, config: &ImportConfig) -> std::result::Result<ParsedEntry, Error> {
+        let time_period = match TimePeriod::from_str(&self.time_period.to_lowercase()) {
+            Some(time_period) => time_period,
+            None => {
+                info!(
+                    "Received invalid time period input, '{}'!",
+                    self.time_period
+                );
+                return Err(Error::EntryInputError("Invalid time period value!".into()));
+            }
+        };
 
-    // Now attempt to parse the ABV string.
-    let abv = match form.abv.as_ref().map(Abv::from_str).transpose() {
-        Ok(abv) => abv.flatten(),
-        Err(e) => {
-            info!(
-                "Received invalid ABV input, '{}'!",
-                form.abv.as_ref().unwrap()
-            );
-            let response = ApiResponse::error_message("Invalid ABV value!");
-            return Either::Left(future::ok(HttpResponse::BadRequest().json(response)));
-        }
-    };
+        // Attempt to parse the quantity string.
+        let quantity = match QuantityRange::from_str(&self.quantity) {
+            Ok(quantity) => quantity,
+            Err(Error::ValidationError(message)) => {
+                info!("Received out of range quantity input, '{}'!", self.quantity);
+                return Err(Error::ValidationError(message));
+            }
+            Err(_) => {
+                info!("Received invalid quantity input, '{}'!", self.quantity);
+                return Err(Error::EntryInputError("Invalid quantity value!".into()));
+            }
+        };
 
-    // Parse the volume string.
-    let volume = match form
-        .volume
-        .as_ref()
-        .map(VolumeContext::from_str)
-        .transpose()
-    {
-        Ok(volume) => volume.flatten(),
-        Err(e) => {
-            info!(
-                "Received invalid Volume input, '{}'!",
-                form.volume.as_ref().unwrap()
-            );
-            let response = ApiResponse::error_message("Invalid Volume value!");
-            return Either::Left(future::ok(HttpResponse::BadRequest().json(response)));
+        // Now attempt to parse the ABV string.
+        let abv = match self.abv.as_ref().map(Abv::from_str).transpose() {
+            Ok(abv) => abv.flatten(),
+            Err(Error::ValidationError(message)) => {
+                info!(
+                    "Received out of range ABV input, '{}'!",
+                    self.abv.as_ref().unwrap()
+                );
+                return Err(Error::ValidationError(message));
+            }
+            Err(_) => {
+                info!(
+                    "Received invalid ABV input, '{}'!",
+                    self.abv.as_ref().unwrap()
+                );
+                return Err(Error::EntryInputError("Invalid ABV value!".into()));
+            }
+        };
+
+        // Parse the volume string.
+        let volume = match self
+            .volume
+            .as_ref()
+            .map(VolumeContext::from_str)
+            .transpose()
+        {
+            Ok(volume) => volume.flatten(),
+            Err(_) => {
+                info!(
+                    "Received invalid Volume input, '{}'!",
+                    self.volume.as_ref().unwrap()
+                );
+                return Err(Error::EntryInputError("Invalid Volume value!".into()));
+            }
+        };
+
+        // Finally, normalize the name
+        let name = self.name.trim();
+
+        // Return an error if the name is empty.
+        if name.is_empty() {
+            return Err(Error::EntryInputError(
+                "Entry name can not be empty!".into(),
+            ));
         }
-    };
 
-    // Finally, normalize the name
-    let name = form.name.trim();
+        // And attempt to derive a multiplier, if needed.
+        let multiplier = config.multiplier(name);
 
-    // Return an error if the name is empty.
-    if name.is_empty() {
-        let response = ApiResponse::error_message("Entry name can not be empty!");
-        return Either::Left(future::ok(HttpResponse::BadRequest().json(response)));
+        let context = self
+            .context
+            .as_deref()
+            .map(parse_context_tags)
+            .unwrap_or_default();
+
+        Ok(ParsedEntry {
+            time_period,
+            context,
+            quantity,
+            abv,
+            volume,
+            name: name.to_string(),
+            multiplier,
+        })
     }
+}
+
+fn new_entry(
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    config: web::Data<ImportConfig>,
+    form: web::Form<EntryForm>,
+) -> impl Future<Output = Result<HttpResponse>> {
+    use futures::future;
 
-    // And attempt to derive a multiplier, if needed.
-    let multiplier = match name.to_lowercase().contains("double") {
-        true => 2.0,
-        false => 1.0,
+    let ParsedEntry {
+        time_period,
+        context,
+        quantity,
+        abv,
+        volume,
+        name,
+        multiplier,
+    } = match form.parse(&config) {
+        Ok(entry) => entry,
+        Err(e) => return Either::Left(future::ok(e.error_response())),
     };
 
     /*********************************************/
@@ -281,7 +1828,7 @@ fn new_entry(
 
     Either::Right(
         // Lookup the drink details if a record exists, otherwise create a new record.
-        get_or_create_drink(&pool, name.to_string(), abv, multiplier)
+        get_or_create_drink(&pool, name, abv, multiplier)
             // Now create a new entry using the drink details.
             .and_then(move |drink| {
                 create_entry(
@@ -289,7 +1836,7 @@ fn new_entry(
                     1,
                     form.drank_on,
                     time_period,
-                    Vec::new(),
+                    context,
                     drink.id,
                     quantity,
                     volume,
@@ -303,10 +1850,7 @@ fn new_entry(
                     match res {
                         // All good, return the entry.
                         Ok(Some(entry)) => {
-                            let output = AggregatedEntry {
-                                aggregate: entry.aggregate(),
-                                entry: entry,
-                            };
+                            let output = AggregatedEntry::new(entry, &standard);
 
                             Ok(ApiResponse::success(output).into())
                         }
@@ -326,7 +1870,81 @@ fn new_entry(
     )
 }
 
+/// Create several entries at once. If any entry is invalid, or fails to save, no entries are created.
+async fn new_entries_batch(
+    pool: web::Data<Pool>,
+    config: web::Data<ImportConfig>,
+    forms: web::Json<Vec<EntryForm>>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    // Validate every entry before touching the database.
+    let mut parsed = Vec::with_capacity(forms.len());
+    for (index, form) in forms.iter().enumerate() {
+        match form.parse(&config) {
+            Ok(entry) => parsed.push((form.drank_on, entry)),
+            Err(e) => {
+                let response = ApiResponse::error_message(format!("Entry {}: {}", index, e));
+                return Ok(HttpResponse::build(e.status_code()).json(response));
+            }
+        }
+    }
+
+    // Each drink is looked up, or created, within the same transaction as the entries,
+    // so a batch which fails to save doesn't leave behind any new drinks.
+    let entries = parsed
+        .into_iter()
+        .map(|(drank_on, entry)| BulkEntry {
+            drink: GetOrCreateDrink {
+                name: entry.name,
+                abv: entry.abv,
+                multiplier: entry.multiplier,
+            },
+            entry: CreateEntry {
+                person_id,
+                drank_on,
+                time_period: entry.time_period,
+                context: entry.context,
+                drink_id: 0,
+                quantity: entry.quantity,
+                volume: entry.volume,
+            },
+        })
+        .collect();
+
+    let created = db::execute(&pool, BulkCreateEntries { entries }).await?;
+
+    Ok(ApiResponse::success_message(format!("Created {} entries", created.len())).into())
+}
+
+/// Route to delete one of the person's entries.
 async fn delete_entry(path: web::Path<i32>, pool: web::Data<Pool>) -> ActixResult<HttpResponse> {
+    let entry = db::execute(
+        &pool,
+        GetEntry {
+            person_id: 1,
+            entry_id: path.into_inner(),
+        },
+    )
+    .await?;
+
+    let entry = match entry {
+        Some(entry) => entry,
+        None => {
+            let response = ApiResponse::error_message("Not found");
+            return Ok(HttpResponse::NotFound().json(response));
+        }
+    };
+
+    db::execute(&pool, DeleteEntry { entry }).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn increment_entry(
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+) -> ActixResult<HttpResponse> {
     use db::Entry;
     // This closure will lookup the full details of the given entry.
     let get_entry = |pool: &Pool, person_id: i32, entry_id: i32| {
@@ -339,9 +1957,18 @@ async fn delete_entry(path: web::Path<i32>, pool: web::Data<Pool>) -> ActixResul
         )
     };
 
-    let delete_entry = |pool: &Pool, entry: Entry| db::execute(&pool, DeleteEntry { entry });
+    let update_entry = |pool: &Pool, entry: Entry| {
+        db::execute(
+            &pool,
+            UpdateEntry {
+                entry,
+                person_id: 1,
+                volume: None,
+            },
+        )
+    };
 
-    let entry = match get_entry(&pool, 1, path.into_inner()).await {
+    let mut entry = match get_entry(&pool, 1, path.into_inner()).await {
         Ok(Some(entry)) => entry,
         Ok(None) => {
             let response = ApiResponse::error_message("Not found");
@@ -353,21 +1980,84 @@ async fn delete_entry(path: web::Path<i32>, pool: web::Data<Pool>) -> ActixResul
         }
     };
 
+    // Increment the min/max quantities by 1.
+    entry.increment();
+
     // Resave the Entry.
-    delete_entry(&pool, entry.clone())
+    update_entry(&pool, entry.clone())
         .and_then(|_| {
             async move {
-                Ok(ApiResponse::success_message("Entry deleted").into())
+                let output = AggregatedEntry::new(entry, &standard);
+
+                Ok(ApiResponse::success(output).into())
             }
         })
         .map_err(|e| actix_web::Error::from(e))
         .await
 }
 
-async fn increment_entry(path: web::Path<i32>, pool: web::Data<Pool>) -> ActixResult<HttpResponse> {
-    use db::Entry;
-    // This closure will lookup the full details of the given entry.
-    let get_entry = |pool: &Pool, person_id: i32, entry_id: i32| {
+#[derive(Deserialize)]
+struct EntryEditForm {
+    pub time_period: Option<String>,
+    pub min_quantity: Option<String>,
+    pub max_quantity: Option<String>,
+}
+
+impl EntryEditForm {
+    /// Apply the fields present in the form to `entry`, returning an error message
+    /// if any are invalid.
+    fn apply(&self, entry: &mut db::Entry) -> std::result::Result<(), &'static str> {
+        if let Some(time_period) = &self.time_period {
+            entry.time = match TimePeriod::from_str(&time_period.to_lowercase()) {
+                Some(time_period) => time_period,
+                None => {
+                    info!("Received invalid time period input, '{}'!", time_period);
+                    return Err("Invalid time period value!");
+                }
+            };
+        }
+
+        // Each bound is a single quantity, ex: "2" or "~2.5".
+        let parse_bound = |bound: &String| match QuantityRange::from_str(bound) {
+            Ok(quantity) => Ok(quantity.min),
+            Err(_) => {
+                info!("Received invalid quantity input, '{}'!", bound);
+                Err("Invalid quantity value!")
+            }
+        };
+
+        let quantity = QuantityRange {
+            min: match &self.min_quantity {
+                Some(min) => parse_bound(min)?,
+                None => entry.min_quantity,
+            },
+            max: match &self.max_quantity {
+                Some(max) => parse_bound(max)?,
+                None => entry.max_quantity,
+            },
+        };
+
+        if quantity.validate().is_err() {
+            return Err("Minimum quantity must not exceed the maximum!");
+        }
+
+        entry.min_quantity = quantity.min;
+        entry.max_quantity = quantity.max;
+
+        Ok(())
+    }
+}
+
+/// Route to correct the time period or quantity of an existing entry.
+async fn edit_entry(
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    form: web::Form<EntryEditForm>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let entry_id = path.into_inner();
+    let get_entry = |pool: &Pool| {
         db::execute(
             &pool,
             GetEntry {
@@ -377,37 +2067,186 @@ async fn increment_entry(path: web::Path<i32>, pool: web::Data<Pool>) -> ActixRe
         )
     };
 
-    let update_entry = |pool: &Pool, entry: Entry| db::execute(&pool, UpdateEntry { entry });
-
-    let mut entry = match get_entry(&pool, 1, path.into_inner()).await {
-        Ok(Some(entry)) => entry,
-        Ok(None) => {
+    let mut entry = match get_entry(&pool).await? {
+        Some(entry) => entry,
+        None => {
             let response = ApiResponse::error_message("Not found");
             return Ok(HttpResponse::NotFound().json(response));
         }
-        Err(e) => {
-            return Ok(HttpResponse::InternalServerError()
-                .json(ApiResponse::fail_message("Internal server error")));
-        }
     };
 
-    // Increment the min/max quantities by 1.
-    entry.increment();
+    if let Err(message) = form.apply(&mut entry) {
+        return Ok(HttpResponse::BadRequest().json(ApiResponse::error_message(message)));
+    }
 
-    // Resave the Entry.
-    update_entry(&pool, entry.clone())
-        .and_then(|_| {
-            async move {
-                let output = AggregatedEntry {
-                    aggregate: entry.aggregate(),
-                    entry: entry,
-                };
+    db::execute(
+        &pool,
+        UpdateEntry {
+            entry,
+            person_id,
+            volume: None,
+        },
+    )
+    .await?;
 
-                Ok(ApiResponse::success(output).into())
+    match get_entry(&pool).await? {
+        Some(entry) => Ok(ApiResponse::success(AggregatedEntry::new(entry, &standard)).into()),
+        None => {
+            let response = ApiResponse::error_message("Not found");
+            Ok(HttpResponse::NotFound().json(response))
+        }
+    }
+}
+
+/// Deserialize a field which may be missing, `null`, or a value, such that
+/// a missing field is `None`, and `null` is `Some(None)`.
+fn deserialize_some<'de, T, D>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    serde::Deserialize::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+struct EntryPatch {
+    /// A new volume string, or `null` to clear the entry's volume.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub volume: Option<Option<String>>,
+}
+
+/// Route to correct details of an existing entry.
+async fn update_entry(
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+    standard: web::Data<DrinkStandard>,
+    patch: web::Json<EntryPatch>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    let entry_id = path.into_inner();
+    let get_entry = |pool: &Pool| {
+        db::execute(
+            &pool,
+            GetEntry {
+                person_id,
+                entry_id,
+            },
+        )
+    };
+
+    let entry = match get_entry(&pool).await? {
+        Some(entry) => entry,
+        None => {
+            let response = ApiResponse::error_message("Not found");
+            return Ok(HttpResponse::NotFound().json(response));
+        }
+    };
+
+    let volume = match &patch.volume {
+        Some(Some(volume)) => match VolumeContext::from_str_with_context(volume, &entry.context) {
+            Ok(Some(volume)) => Some(Some(volume)),
+            _ => {
+                info!("Received invalid Volume input, '{}'!", volume);
+                let response = ApiResponse::error_message("Invalid Volume value!");
+                return Ok(HttpResponse::BadRequest().json(response));
             }
-        })
-        .map_err(|e| actix_web::Error::from(e))
-        .await
+        },
+        Some(None) => Some(None),
+        None => None,
+    };
+
+    db::execute(
+        &pool,
+        UpdateEntry {
+            entry,
+            person_id,
+            volume,
+        },
+    )
+    .await?;
+
+    // Reload the entry so that the response reflects the recalculated volume.
+    match get_entry(&pool).await? {
+        Some(entry) => Ok(ApiResponse::success(AggregatedEntry::new(entry, &standard)).into()),
+        None => {
+            let response = ApiResponse::error_message("Not found");
+            Ok(HttpResponse::NotFound().json(response))
+        }
+    }
+}
+
+/// Route to get the history of changes made to an entry.
+async fn get_entry_audit_log(
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+    AuthenticatedPerson(person_id): AuthenticatedPerson,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "audit_log")]
+    struct AuditLog(Vec<models::AuditLogEntry>);
+
+    let entry_id = path.into_inner();
+    let entry = db::execute(
+        &pool,
+        GetEntry {
+            person_id,
+            entry_id,
+        },
+    )
+    .await?;
+
+    if entry.is_none() {
+        let response = ApiResponse::error_message("Not found");
+        return Ok(HttpResponse::NotFound().json(response));
+    }
+
+    let log = db::execute(
+        &pool,
+        GetEntryAuditLog {
+            entry_id,
+            person_id,
+        },
+    )
+    .await?;
+
+    Ok(ApiResponse::success(AuditLog(log)).into())
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// Route to exchange a username and password for a signed token.
+async fn create_token(
+    credentials: web::Json<TokenRequest>,
+    secret: web::Data<JwtSecret>,
+    pool: web::Data<Pool>,
+) -> ActixResult<HttpResponse> {
+    #[derive(Serialize)]
+    #[serde(rename = "token")]
+    struct Token {
+        token: String,
+    }
+
+    let credentials = credentials.into_inner();
+    let person = db::execute(
+        &pool,
+        GetPersonByUsername {
+            username: credentials.username,
+        },
+    )
+    .await?;
+
+    let person = match person {
+        Some(person) if auth::verify_password(&person, &credentials.password)? => person,
+        _ => return Err(Error::Unauthorized.into()),
+    };
+
+    let token = auth::create_token(person.id, &secret.0)?;
+
+    Ok(ApiResponse::success(Token { token }).into())
 }
 
 #[actix_rt::main]
@@ -431,11 +2270,37 @@ async fn main() -> std::io::Result<()> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     let pool = Pool::new(manager).expect("Failed to create database connection pool!");
 
+    // The secret used to sign authentication tokens.
+    let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set!");
+
+    // How long to wait for in-flight requests to finish when shutting down.
+    let shutdown_timeout =
+        u64::from_str(&std::env::var("SHUTDOWN_TIMEOUT_SECS").unwrap_or("30".into()))
+            .expect("Failed to parse $SHUTDOWN_TIMEOUT_SECS!");
+
+    // The amount of alcohol in one standard drink, in mL; 14 mL is used by default.
+    let standard = match std::env::var("DRINK_STANDARD_ML") {
+        Ok(ml) => DrinkStandard {
+            ml_per_alcohol: f32::from_str(&ml).expect("Failed to parse $DRINK_STANDARD_ML!"),
+        },
+        Err(_) => DrinkStandard::default(),
+    };
+
+    // The keywords used to detect doubles, triples, etc. in new drink names.
+    let import_config = match std::env::var("IMPORT_CONFIG") {
+        Ok(path) => ImportConfig::from_file(path).expect("Failed to load $IMPORT_CONFIG!"),
+        Err(_) => ImportConfig::default(),
+    };
+
     info!("Listening on {}", listen_addr);
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
             .data(pool.clone())
+            .data(JwtSecret(jwt_secret.clone()))
+            .data(standard)
+            .data(import_config.clone())
+            .wrap(JwtMiddleware::new(jwt_secret.clone()))
             .wrap(Logger::default())
             .wrap(Cors::default())
             .route("/", web::get().to(index))
@@ -444,10 +2309,82 @@ async fn main() -> std::io::Result<()> {
                 web::scope("/drinks")
                     .route("", web::get().to(get_entries))
                     .route("", web::post().to(new_entry))
+                    .route("/no-volume", web::get().to(get_entries_without_volume))
+                    .route(
+                        "/missing-volume-ml",
+                        web::get().to(get_entries_missing_volume_ml),
+                    )
+                    .route("/fix-volume-ml", web::post().to(fix_entries_volume_ml))
+                    .route("/coverage", web::get().to(get_coverage))
+                    .route("/count-by-date", web::get().to(get_count_by_date))
+                    .route("/definitions", web::get().to(get_drink_definitions))
+                    .route("/definitions/merge", web::post().to(merge_drinks))
+                    .route(
+                        "/definitions/unused",
+                        web::delete().to(delete_unused_drinks),
+                    )
+                    .route("/similar", web::get().to(get_similar_drinks))
+                    .route("/search", web::get().to(search_drinks))
+                    .route("/budget", web::get().to(get_budget))
+                    .route("/abv-weighted-units", web::get().to(get_weighted_units))
+                    .route("/abv-profile", web::get().to(get_abv_profile))
+                    .route("/abv-timeline", web::get().to(get_abv_timeline))
+                    .route("/volume-breakdown", web::get().to(get_volume_breakdown))
+                    .route("/entry-gaps", web::get().to(get_entry_gaps))
+                    .route(
+                        "/frequency-histogram",
+                        web::get().to(get_frequency_histogram),
+                    )
+                    .route("/streak-history", web::get().to(get_streak_history))
+                    .route("/percentile", web::get().to(get_percentile))
+                    .route("/dry-months", web::get().to(get_dry_months))
+                    .route("/calendar-matrix", web::get().to(get_calendar_matrix))
+                    .route("/year/{year}", web::get().to(get_year_summary))
+                    .route("/yearly-summary", web::get().to(get_yearly_summary))
+                    .route(
+                        "/by-occasion/{date}/{time_period}",
+                        web::get().to(get_entries_by_occasion),
+                    )
+                    .route("/time-distribution", web::get().to(get_time_distribution))
+                    .route("/weekend-vs-weekday", web::get().to(get_weekend_vs_weekday))
+                    .route("/stats", web::get().to(get_stats))
+                    .route("/sessions", web::get().to(get_sessions))
+                    .route("/export.json", web::get().to(export_entries_json))
+                    .route("/export.ical", web::get().to(export_sessions_ical))
+                    .route("/summary", web::get().to(get_summary))
+                    .route("/summary/all-time", web::get().to(get_all_time_summary))
+                    .route("/context-heatmap", web::get().to(get_context_heatmap))
+                    .route("/context-breakdown", web::get().to(get_context_breakdown))
+                    .route("/context-timeline", web::get().to(get_context_timeline))
+                    .route(
+                        "/context-suggestions",
+                        web::get().to(get_context_suggestions),
+                    )
                     .route("/{id}", web::delete().to(delete_entry))
-                    .route("/{id}/increment", web::put().to(increment_entry)),
+                    .route("/{id}", web::put().to(edit_entry))
+                    .route("/{id}/increment", web::put().to(increment_entry))
+                    .route("/entry/{id}/audit-log", web::get().to(get_entry_audit_log))
+                    .route("/{id}/history", web::get().to(get_drink_history)),
+            )
+            .service(
+                web::scope("/persons").route("/{id}/drinks/export", web::get().to(export_entries)),
+            )
+            .service(
+                web::scope("/entries")
+                    .route("/batch", web::post().to(new_entries_batch))
+                    .route("/{id}", web::patch().to(update_entry)),
+            )
+            .service(
+                web::scope("/reports")
+                    .route("/weekly", web::get().to(get_weekly_report))
+                    .route("/monthly", web::get().to(get_monthly_report)),
             )
-            .service(web::scope("/days").route("/{date}", web::get().to(get_entries_by_date)))
+            .service(
+                web::scope("/days")
+                    .route("/{date}", web::get().to(get_entries_by_date))
+                    .route("/{date}/summary", web::get().to(get_day_summary)),
+            )
+            .service(web::scope("/auth").route("/token", web::post().to(create_token)))
 
         /*.service(
             web::scope("/drink")
@@ -471,6 +2408,44 @@ async fn main() -> std::io::Result<()> {
         )*/
     })
     .bind(&listen_addr)?
-    .run()
-    .await
+    .shutdown_timeout(shutdown_timeout)
+    .disable_signals()
+    .run();
+
+    // Stop accepting connections once signaled, letting in-flight requests finish.
+    let handle = server.clone();
+    actix_rt::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutting down gracefully...");
+        handle.stop(true).await;
+    });
+
+    server.await?;
+    info!("Shutdown complete");
+
+    Ok(())
+}
+
+/// Wait for either SIGINT or, on unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = actix_rt::signal::ctrl_c().map(|_| ());
+
+    #[cfg(unix)]
+    {
+        use actix_rt::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                let terminate = terminate.recv().map(|_| ());
+                futures::future::select(Box::pin(ctrl_c), Box::pin(terminate)).await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                ctrl_c.await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    ctrl_c.await;
 }
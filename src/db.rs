@@ -5,17 +5,21 @@ use chrono::{DateTime, Duration, Utc};
 use diesel;
 use diesel::prelude::*;
 use diesel::r2d2;
-use diesel::sql_types::Text;
+use diesel::sql_types::{BigInt, Date, Float, Integer, Nullable, Text};
 use futures::future::Future;
 use futures::prelude::*;
 use serde::Serialize;
 
 use std::marker::Send;
 
+// The `Connection` type alias below shadows this trait, which provides `transaction`.
+use diesel::Connection as _;
+
 use crate::error::{Error, Result};
 use crate::import::{Abv, QuantityRange, VolumeContext};
 use crate::models;
-use crate::models::{ApproxF32, Drink, LiquidVolume, TimePeriod};
+use crate::models::{ApproxF32, Drink, LiquidVolume, TimePeriod, VolumeUnit, APPROX_MODIFIER};
+use crate::reports::DrinkStandard;
 use crate::schema;
 
 pub type Pool = r2d2::Pool<r2d2::ConnectionManager<PgConnection>>;
@@ -31,6 +35,15 @@ pub trait Query {
     fn execute(&self, conn: Connection) -> Result<Self::Output>;
 }
 
+/// Allow queries to be chosen at runtime, so long as they produce the same output.
+impl<O: Send> Query for Box<dyn Query<Output = O> + Send> {
+    type Output = O;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        (**self).execute(conn)
+    }
+}
+
 pub fn execute<T: Query + Send + 'static>(
     pool: &Pool,
     query: T,
@@ -78,25 +91,47 @@ pub struct Entry {
     pub updated_at: DateTime<Utc>,
 }
 
+// `Entry` is selected from a join, so `Identifiable` can not be derived.
+impl diesel::associations::HasTable for Entry {
+    type Table = schema::entry::table;
+
+    fn table() -> Self::Table {
+        schema::entry::table
+    }
+}
+
+impl<'a> diesel::associations::Identifiable for &'a Entry {
+    type Id = &'a i32;
+
+    fn id(self) -> Self::Id {
+        &self.id
+    }
+}
+
 impl Entry {
     #[inline]
     pub fn min_quantity(&self) -> f32 {
-        self.min_quantity.min()
+        self.min_quantity.lower_bound()
     }
 
     #[inline]
     pub fn max_quantity(&self) -> f32 {
-        self.max_quantity.max()
+        self.max_quantity.upper_bound()
     }
 
     /// Get the min ABV range as a float
     pub fn min_abv(&self) -> Option<f32> {
-        self.min_abv.map(|abv| abv.min())
+        self.min_abv.map(|abv| abv.lower_bound())
     }
 
     /// Get the max ABV range as a float
     pub fn max_abv(&self) -> Option<f32> {
-        self.max_abv.map(|abv| abv.max())
+        self.max_abv.map(|abv| abv.upper_bound())
+    }
+
+    /// Get the ABV range of this entry, if both bounds are present.
+    pub fn abv(&self) -> Option<Abv> {
+        Abv::from_approx_f32_pair(self.min_abv, self.max_abv)
     }
 
     /// Check if this entry has any ABV information.
@@ -113,6 +148,23 @@ impl Entry {
         self.volume.is_some()
     }
 
+    /// Get the volume of this entry converted to `unit`, if known.
+    pub fn volume_in_unit(&self, unit: VolumeUnit) -> Option<LiquidVolume> {
+        self.volume.map(|volume| volume.convert_to(unit))
+    }
+
+    /// Get the volume of this entry in fluid ounces, if known.
+    pub fn volume_fl_oz(&self) -> Option<LiquidVolume> {
+        self.volume_in_unit(VolumeUnit::FlOz)
+    }
+
+    /// Get the volume of this entry in mL, if known.
+    ///
+    /// This is converted from `volume`, rather than read from the stored `volume_ml` field.
+    pub fn volume_ml(&self) -> Option<LiquidVolume> {
+        self.volume_in_unit(VolumeUnit::mL)
+    }
+
     /// Increment the min/max quantity values by 1.0.
     pub fn increment(&mut self) {
         self.min_quantity.increment();
@@ -120,6 +172,43 @@ impl Entry {
     }
 }
 
+/// The columns selected from `entry` joined with `drink`, for loading an `Entry`.
+type EntryColumns = (
+    schema::entry::id,
+    schema::entry::drank_on,
+    schema::entry::time_period,
+    schema::entry::context,
+    schema::entry::drink_id,
+    schema::drink::name,
+    schema::drink::min_abv,
+    schema::drink::max_abv,
+    schema::drink::multiplier,
+    schema::entry::min_quantity,
+    schema::entry::max_quantity,
+    schema::entry::volume,
+    schema::entry::volume_ml,
+    schema::entry::created_at,
+    schema::entry::updated_at,
+);
+
+const ENTRY_COLUMNS: EntryColumns = (
+    schema::entry::id,
+    schema::entry::drank_on,
+    schema::entry::time_period,
+    schema::entry::context,
+    schema::entry::drink_id,
+    schema::drink::name,
+    schema::drink::min_abv,
+    schema::drink::max_abv,
+    schema::drink::multiplier,
+    schema::entry::min_quantity,
+    schema::entry::max_quantity,
+    schema::entry::volume,
+    schema::entry::volume_ml,
+    schema::entry::created_at,
+    schema::entry::updated_at,
+);
+
 /*************************************/
 /** Get Drinks query                **/
 /*************************************/
@@ -128,16 +217,71 @@ impl Entry {
 pub struct GetDrinks {
     pub person_id: i32,
     pub date_range: Option<(NaiveDate, NaiveDate)>,
+    /// Only return entries of this specific drink.
+    pub drink_id: Option<i32>,
+    /// Only return entries from this time of day.
+    pub time_period: Option<TimePeriod>,
+    /// Only return entries tagged with this context.
+    pub context_contains: Option<String>,
+    /// Return at most this many entries.
+    pub limit: Option<i64>,
+    /// Skip this many entries, ex: to get the second page of entries.
+    pub offset: Option<i64>,
+}
+
+impl GetDrinks {
+    /// Ensure the date range, if any, does not end before it starts.
+    fn validate(&self) -> Result<()> {
+        match self.date_range {
+            Some((start, end)) if start > end => Err(Error::InvalidDateRange { start, end }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Build the query of entries joined with their drinks, restricted by every filter, but
+    /// not the `limit` and `offset`; shared with `CountDrinks` so that both match the same entries.
+    fn filtered(&self) -> FilteredEntries {
+        use crate::schema::{drink, entry};
+
+        let mut query = entry::table
+            .inner_join(drink::table)
+            .filter(entry::person_id.eq(self.person_id))
+            .into_boxed();
+
+        if let Some((start, end)) = self.date_range {
+            query = query.filter(entry::drank_on.ge(start).and(entry::drank_on.le(end)));
+        }
+
+        if let Some(filter_drink_id) = self.drink_id {
+            query = query.filter(entry::drink_id.eq(filter_drink_id));
+        }
+
+        if let Some(filter_time_period) = self.time_period {
+            query = query.filter(entry::time_period.eq(filter_time_period));
+        }
+
+        if let Some(tag) = self.context_contains.as_ref() {
+            query = query.filter(entry::context.contains(vec![tag.to_lowercase()]));
+        }
+
+        query
+    }
 }
 
+/// Entries joined with their drinks, as a boxed query which can be filtered at runtime.
+type FilteredEntries = diesel::helper_types::IntoBoxed<
+    'static,
+    diesel::helper_types::InnerJoin<schema::entry::table, schema::drink::table>,
+    diesel::pg::Pg,
+>;
+
 impl Query for GetDrinks {
     type Output = Vec<Entry>;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use crate::schema::drink;
-        use crate::schema::drink::dsl::*;
+        self.validate()?;
+
         use crate::schema::entry;
-        use crate::schema::entry::dsl::*;
 
         /* let filter = match self.date_range {
             Some((start, end)) => Box::new(
@@ -149,221 +293,1557 @@ impl Query for GetDrinks {
             None => Box::new(entry::person_id.eq(&self.person_id)),
         };*/
 
-        let mut query = entry
-            .inner_join(drink)
-            .select((
-                entry::id,
-                entry::drank_on,
-                entry::time_period,
-                entry::context,
-                entry::drink_id,
-                drink::name,
-                drink::min_abv,
-                drink::max_abv,
-                drink::multiplier,
-                entry::min_quantity,
-                entry::max_quantity,
-                entry::volume,
-                entry::volume_ml,
-                entry::created_at,
-                entry::updated_at,
-            ))
-            .filter(entry::person_id.eq(&self.person_id))
-            .into_boxed();
+        let mut query = self.filtered().select(ENTRY_COLUMNS);
 
-        if let Some((start, end)) = self.date_range {
-            query = query.filter(entry::drank_on.ge(start).and(entry::drank_on.le(end)));
+        if let Some(limit) = self.limit {
+            query = query.limit(limit);
+        }
+
+        if let Some(offset) = self.offset {
+            query = query.offset(offset);
         }
+
+        // Entries on the same occasion are ordered by id, so pages never overlap or skip entries.
         Ok(query
             .order(entry::drank_on.desc())
             .then_order_by(entry::time_period.asc())
+            .then_order_by(entry::id.asc())
             .load::<Entry>(&conn)?)
     }
 }
 
+/// Count every entry matching the filters of a `GetDrinks` query, ignoring its `limit` and
+/// `offset`; ex: to show how many pages of entries there are.
+#[derive(Clone)]
+pub struct CountDrinks {
+    pub query: GetDrinks,
+}
+
+impl Query for CountDrinks {
+    type Output = i64;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        self.query.validate()?;
+
+        Ok(self
+            .query
+            .filtered()
+            .select(diesel::dsl::count_star())
+            .get_result::<i64>(&conn)?)
+    }
+}
+
 /*************************************/
-/** Get Entry query                 **/
+/** Get Drinks without volume query **/
 /*************************************/
 
+/// Get all entries which have no volume information.
 #[derive(Clone)]
-pub struct GetEntry {
+pub struct GetDrinksWithoutVolume {
     pub person_id: i32,
-    pub entry_id: i32,
 }
 
-impl Query for GetEntry {
-    type Output = Option<Entry>;
+impl Query for GetDrinksWithoutVolume {
+    type Output = Vec<Entry>;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use crate::schema::drink;
         use crate::schema::drink::dsl::*;
         use crate::schema::entry;
         use crate::schema::entry::dsl::*;
 
         Ok(entry
             .inner_join(drink)
-            .select((
-                entry::id,
-                entry::drank_on,
-                entry::time_period,
-                entry::context,
-                entry::drink_id,
-                drink::name,
-                drink::min_abv,
-                drink::max_abv,
-                drink::multiplier,
-                entry::min_quantity,
-                entry::max_quantity,
-                entry::volume,
-                entry::volume_ml,
-                entry::created_at,
-                entry::updated_at,
-            ))
-            .filter(
-                entry::person_id
-                    .eq(&self.person_id)
-                    .and(entry::id.eq(&self.entry_id)),
-            )
-            .first::<Entry>(&conn)
-            .optional()?)
+            .select(ENTRY_COLUMNS)
+            .filter(entry::person_id.eq(&self.person_id))
+            .filter(entry::volume.is_null())
+            .order(entry::drank_on.desc())
+            .then_order_by(entry::time_period.asc())
+            .load::<Entry>(&conn)?)
     }
 }
 
 /*************************************/
+/** Get Entries missing mL query    **/
 /*************************************/
 
-pub struct GetDrink {
-    // @TODO: Associate with person ID?
-    pub name: String,
-    pub abv: Option<Abv>,
+/// Get all entries which have a volume, but are missing the volume in mL.
+#[derive(Clone)]
+pub struct GetEntriesMissingVolumeMl {
+    pub person_id: i32,
 }
 
-impl Query for GetDrink {
-    type Output = Option<Drink>;
+impl Query for GetEntriesMissingVolumeMl {
+    type Output = Vec<Entry>;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use super::schema::drink::dsl::*;
+        use crate::schema::drink::dsl::*;
+        use crate::schema::entry;
+        use crate::schema::entry::dsl::*;
 
-        let min = self.abv.as_ref().map(|abv| abv.min);
-        let max = self.abv.as_ref().map(|abv| abv.max);
+        Ok(entry
+            .inner_join(drink)
+            .select(ENTRY_COLUMNS)
+            .filter(entry::person_id.eq(&self.person_id))
+            .filter(entry::volume.is_not_null())
+            .filter(entry::volume_ml.is_null())
+            .order(entry::drank_on.desc())
+            .then_order_by(entry::time_period.asc())
+            .load::<Entry>(&conn)?)
+    }
+}
 
-        Ok(drink
-            .filter(
-                lower(name)
-                    .eq(&self.name.to_lowercase())
-                    .and(min_abv.eq(&min))
-                    .and(max_abv.eq(&max)),
-            )
-            .first::<Drink>(&conn)
-            .optional()?)
+/*************************************/
+/** Get All Days query              **/
+/*************************************/
+
+/// Get every distinct date on which an entry was recorded, in ascending order.
+#[derive(Clone)]
+pub struct GetAllDays {
+    pub person_id: i32,
+}
+
+impl Query for GetAllDays {
+    type Output = Vec<NaiveDate>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use crate::schema::entry::dsl::*;
+
+        Ok(entry
+            .filter(person_id.eq(&self.person_id))
+            .select(drank_on)
+            .distinct()
+            .order(drank_on.asc())
+            .load::<NaiveDate>(&conn)?)
     }
 }
 
 /*************************************/
+/** Get Drinks Count By Date query  **/
 /*************************************/
 
-pub struct CreateDrink {
-    pub name: String,
-    pub abv: Option<Abv>,
-    pub multiplier: f32,
+#[derive(QueryableByName)]
+struct DateCount {
+    #[sql_type = "Date"]
+    drank_on: NaiveDate,
+    #[sql_type = "BigInt"]
+    count: i64,
 }
 
-impl Query for CreateDrink {
-    type Output = Drink;
+/// Count the entries on each day from `start` through `end`, inclusive.
+/// Days without entries are omitted.
+#[derive(Clone)]
+pub struct GetDrinksCountByDate {
+    pub person_id: i32,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Query for GetDrinksCountByDate {
+    type Output = Vec<(NaiveDate, i64)>;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use super::schema::drink;
+        if self.start > self.end {
+            return Err(Error::InvalidDateRange {
+                start: self.start,
+                end: self.end,
+            });
+        }
 
-        let min = self.abv.as_ref().map(|abv| abv.min);
-        let max = self.abv.as_ref().map(|abv| abv.max);
+        Ok(diesel::sql_query(
+            "SELECT drank_on, COUNT(*) AS count
+            FROM entry
+            WHERE person_id = $1 AND drank_on BETWEEN $2 AND $3
+            GROUP BY drank_on
+            ORDER BY drank_on",
+        )
+        .bind::<Integer, _>(self.person_id)
+        .bind::<Date, _>(self.start)
+        .bind::<Date, _>(self.end)
+        .load::<DateCount>(&conn)?
+        .into_iter()
+        .map(|day| (day.drank_on, day.count))
+        .collect())
+    }
+}
 
-        let new_drink = super::models::NewDrink {
-            name: self.name.as_str(),
+/*************************************/
+/** Get Context Suggestions query   **/
+/*************************************/
 
-            min_abv: min,
-            max_abv: max,
+#[derive(QueryableByName)]
+struct ContextTag {
+    #[sql_type = "Text"]
+    tag: String,
+}
 
-            multiplier: self.multiplier,
-        };
+/// Find up to `limit` distinct context tags of the person's entries
+/// which begin with `prefix`, ignoring case, in alphabetical order.
+#[derive(Clone)]
+pub struct GetContextSuggestions {
+    pub person_id: i32,
+    pub prefix: String,
+    pub limit: u32,
+}
 
-        Ok(diesel::insert_into(drink::table)
-            .values(&new_drink)
-            .get_result(&conn)?)
+impl Query for GetContextSuggestions {
+    type Output = Vec<String>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        // Match the entire prefix, however long it is.
+        let pattern = name_prefix_pattern(&self.prefix, usize::MAX);
+
+        // `unnest` can't be used in a `WHERE` clause, so the tags are unnested in a subquery.
+        Ok(diesel::sql_query(
+            "SELECT DISTINCT tag
+            FROM (SELECT unnest(context) AS tag FROM entry WHERE person_id = $1) AS tags
+            WHERE lower(tag) LIKE $2
+            ORDER BY tag
+            LIMIT $3",
+        )
+        .bind::<Integer, _>(self.person_id)
+        .bind::<Text, _>(pattern)
+        .bind::<BigInt, _>(self.limit as i64)
+        .load::<ContextTag>(&conn)?
+        .into_iter()
+        .map(|context| context.tag)
+        .collect())
     }
 }
 
 /*************************************/
+/** Get Coverage query              **/
 /*************************************/
 
-pub struct CreateEntry {
+/// Counts of how many entries have enough information to calculate standard drinks.
+#[derive(QueryableByName, Serialize)]
+#[serde(rename = "coverage")]
+pub struct Coverage {
+    #[sql_type = "BigInt"]
+    pub total: i64,
+    #[sql_type = "BigInt"]
+    pub with_abv: i64,
+    #[sql_type = "BigInt"]
+    pub with_volume: i64,
+    #[sql_type = "BigInt"]
+    pub with_both: i64,
+    #[sql_type = "BigInt"]
+    pub with_neither: i64,
+}
+
+#[derive(Clone)]
+pub struct GetCoverage {
     pub person_id: i32,
-    pub drank_on: NaiveDate,
-    pub time_period: models::TimePeriod,
-    pub context: Vec<String>,
-    pub drink_id: i32,
-    pub quantity: QuantityRange,
-    pub volume: Option<VolumeContext>,
 }
 
-impl Query for CreateEntry {
-    type Output = models::PlainEntry;
+impl Query for GetCoverage {
+    type Output = Coverage;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use schema::entry;
+        Ok(diesel::sql_query(
+            "SELECT
+                COUNT(*) AS total,
+                COUNT(CASE WHEN d.min_abv IS NOT NULL THEN 1 END) AS with_abv,
+                COUNT(CASE WHEN e.volume IS NOT NULL THEN 1 END) AS with_volume,
+                COUNT(CASE WHEN d.min_abv IS NOT NULL AND e.volume IS NOT NULL THEN 1 END) AS with_both,
+                COUNT(CASE WHEN d.min_abv IS NULL AND e.volume IS NULL THEN 1 END) AS with_neither
+            FROM entry e
+            INNER JOIN drink d ON d.id = e.drink_id
+            WHERE e.person_id = $1",
+        )
+        .bind::<Integer, _>(self.person_id)
+        .get_result::<Coverage>(&conn)?)
+    }
+}
 
-        let new_entry = models::NewEntry {
-            person_id: self.person_id,
-            drank_on: &self.drank_on,
-            time_period: &self.time_period,
-            context: &self.context,
-            drink_id: self.drink_id,
-            min_quantity: &self.quantity.min,
-            max_quantity: &self.quantity.max,
-            volume: self.volume.as_ref().map(|v| v.volume),
-            volume_ml: self.volume.as_ref().map(|v| v.volume.to_ml()),
+/*************************************/
+/** Get Summary query               **/
+/*************************************/
+
+/// All-time totals of a person's entries.
+///
+/// Only entries with a volume in mL count towards the total volume.
+#[derive(QueryableByName, Serialize, Debug)]
+#[serde(rename = "summary")]
+pub struct Summary {
+    #[sql_type = "BigInt"]
+    pub total_entries: i64,
+    #[sql_type = "Float"]
+    pub min_drinks: f32,
+    #[sql_type = "Float"]
+    pub max_drinks: f32,
+    #[sql_type = "Float"]
+    pub min_volume_ml: f32,
+    #[sql_type = "Float"]
+    pub max_volume_ml: f32,
+    #[sql_type = "Nullable<Date>"]
+    pub first_entry: Option<NaiveDate>,
+    #[sql_type = "Nullable<Date>"]
+    pub last_entry: Option<NaiveDate>,
+}
+
+/// Calculate the `Summary` of a person's entries in the database,
+/// rather than loading every entry.
+#[derive(Clone)]
+pub struct GetSummary {
+    pub person_id: i32,
+    pub standard: DrinkStandard,
+}
+
+/// SQL for the lower or upper bound of the `REALAPPROX` `value`,
+/// the same as `ApproxF32::lower_bound` and `ApproxF32::upper_bound`.
+fn approx_bound_sql(value: &str, upper: bool) -> String {
+    let modifier = if upper {
+        1.0 + APPROX_MODIFIER
+    } else {
+        1.0 - APPROX_MODIFIER
+    };
+
+    format!(
+        "({value}).val * (CASE WHEN ({value}).is_approximate THEN {modifier} ELSE 1 END)",
+        value = value,
+        modifier = modifier
+    )
+}
+
+/// SQL for the minimum or maximum number of drinks of an entry `e` of the drink `d`,
+/// calculated the same as the `DrinkAggregator` for `Entry`.
+///
+/// `ml_per_drink` is the parameter holding the `DrinkStandard::ml_per_alcohol`; ex: `$2`.
+fn drinks_sql(upper: bool, ml_per_drink: &str) -> String {
+    let (quantity, abv) = if upper {
+        ("e.max_quantity", "d.max_abv")
+    } else {
+        ("e.min_quantity", "d.min_abv")
+    };
+
+    format!(
+        "CASE
+            WHEN d.min_abv IS NULL OR e.volume IS NULL OR e.volume_ml IS NULL
+                THEN {quantity} * d.multiplier
+            ELSE {quantity} * ({abv} / 100) * {volume} / {ml_per_drink}
+        END",
+        quantity = approx_bound_sql(quantity, upper),
+        abv = approx_bound_sql(abv, upper),
+        volume = approx_bound_sql("(e.volume_ml).volume", upper),
+        ml_per_drink = ml_per_drink,
+    )
+}
+
+impl Query for GetSummary {
+    type Output = Summary;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        let volume = |upper: bool| {
+            let quantity = if upper {
+                "e.max_quantity"
+            } else {
+                "e.min_quantity"
+            };
+
+            format!(
+                "{quantity} * {volume} * d.multiplier",
+                quantity = approx_bound_sql(quantity, upper),
+                volume = approx_bound_sql("(e.volume_ml).volume", upper),
+            )
         };
 
-        Ok(diesel::insert_into(entry::table)
-            .values(&new_entry)
-            .get_result(&conn)?)
+        let sql = format!(
+            "SELECT
+                COUNT(*) AS total_entries,
+                CAST(COALESCE(SUM({min_drinks}), 0) AS REAL) AS min_drinks,
+                CAST(COALESCE(SUM({max_drinks}), 0) AS REAL) AS max_drinks,
+                CAST(COALESCE(SUM({min_volume}), 0) AS REAL) AS min_volume_ml,
+                CAST(COALESCE(SUM({max_volume}), 0) AS REAL) AS max_volume_ml,
+                MIN(e.drank_on) AS first_entry,
+                MAX(e.drank_on) AS last_entry
+            FROM entry e
+            INNER JOIN drink d ON d.id = e.drink_id
+            WHERE e.person_id = $1",
+            min_drinks = drinks_sql(false, "$2"),
+            max_drinks = drinks_sql(true, "$2"),
+            min_volume = volume(false),
+            max_volume = volume(true),
+        );
+
+        Ok(diesel::sql_query(sql)
+            .bind::<Integer, _>(self.person_id)
+            .bind::<Float, _>(self.standard.ml_per_alcohol)
+            .get_result::<Summary>(&conn)?)
     }
 }
 
-pub struct DeleteEntry {
-    pub entry: Entry,
+/*************************************/
+/** Get Weekly Report query         **/
+/*************************************/
+
+/// The totals of the entries of a single week, starting on Monday.
+#[derive(QueryableByName, Serialize, Debug)]
+pub struct WeeklyRow {
+    #[sql_type = "Date"]
+    pub week_start: NaiveDate,
+    #[sql_type = "BigInt"]
+    pub entry_count: i64,
+    #[sql_type = "Float"]
+    pub min_drinks: f32,
+    #[sql_type = "Float"]
+    pub max_drinks: f32,
 }
 
-impl Query for DeleteEntry {
-    type Output = ();
+/// Total the person's entries by week, optionally only those drank in `year`.
+/// Weeks without entries are omitted.
+#[derive(Clone)]
+pub struct GetWeeklyReport {
+    pub person_id: i32,
+    pub year: Option<i32>,
+    pub standard: DrinkStandard,
+}
+
+impl Query for GetWeeklyReport {
+    type Output = Vec<WeeklyRow>;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use schema::entry::dsl::*;
+        let sql = format!(
+            "SELECT
+                CAST(DATE_TRUNC('week', e.drank_on) AS DATE) AS week_start,
+                COUNT(*) AS entry_count,
+                CAST(SUM({min_drinks}) AS REAL) AS min_drinks,
+                CAST(SUM({max_drinks}) AS REAL) AS max_drinks
+            FROM entry e
+            INNER JOIN drink d ON d.id = e.drink_id
+            WHERE e.person_id = $1
+                AND ($3 IS NULL OR EXTRACT(YEAR FROM e.drank_on) = $3)
+            GROUP BY week_start
+            ORDER BY week_start",
+            min_drinks = drinks_sql(false, "$2"),
+            max_drinks = drinks_sql(true, "$2"),
+        );
 
-        Ok(diesel::delete(entry.find(self.entry.id))
-            .execute(&conn)
-            .map(|_qs| ())?)
+        Ok(diesel::sql_query(sql)
+            .bind::<Integer, _>(self.person_id)
+            .bind::<Float, _>(self.standard.ml_per_alcohol)
+            .bind::<Nullable<Integer>, _>(self.year)
+            .load::<WeeklyRow>(&conn)?)
     }
 }
 
+/*************************************/
+/** Get Monthly Report query        **/
+/*************************************/
 
-pub struct UpdateEntry {
-    pub entry: Entry,
+/// The totals of the entries of a single month.
+#[derive(QueryableByName, Serialize, Debug)]
+pub struct MonthlyRow {
+    /// The first day of the month.
+    #[sql_type = "Date"]
+    pub month: NaiveDate,
+    #[sql_type = "BigInt"]
+    pub entry_count: i64,
+    #[sql_type = "Float"]
+    pub min_drinks: f32,
+    #[sql_type = "Float"]
+    pub max_drinks: f32,
+    #[sql_type = "BigInt"]
+    pub distinct_drinks: i64,
 }
 
-impl Query for UpdateEntry {
-    type Output = ();
+/// Total the person's entries by month. Months without entries are omitted.
+#[derive(Clone)]
+pub struct GetMonthlyReport {
+    pub person_id: i32,
+    pub standard: DrinkStandard,
+}
+
+impl Query for GetMonthlyReport {
+    type Output = Vec<MonthlyRow>;
 
     fn execute(&self, conn: Connection) -> Result<Self::Output> {
-        use schema::entry;
-        use schema::entry::dsl::*;
+        let sql = format!(
+            "SELECT
+                CAST(DATE_TRUNC('month', e.drank_on) AS DATE) AS month,
+                COUNT(*) AS entry_count,
+                CAST(SUM({min_drinks}) AS REAL) AS min_drinks,
+                CAST(SUM({max_drinks}) AS REAL) AS max_drinks,
+                COUNT(DISTINCT e.drink_id) AS distinct_drinks
+            FROM entry e
+            INNER JOIN drink d ON d.id = e.drink_id
+            WHERE e.person_id = $1
+            GROUP BY month
+            ORDER BY month",
+            min_drinks = drinks_sql(false, "$2"),
+            max_drinks = drinks_sql(true, "$2"),
+        );
 
-        Ok(diesel::update(entry.find(self.entry.id))
-            .set((
-                time_period.eq(&self.entry.time),
-                min_quantity.eq(&self.entry.min_quantity),
-                max_quantity.eq(&self.entry.max_quantity),
-            ))
-            .execute(&conn)
-            .map(|_qs| ())?)
+        Ok(diesel::sql_query(sql)
+            .bind::<Integer, _>(self.person_id)
+            .bind::<Float, _>(self.standard.ml_per_alcohol)
+            .load::<MonthlyRow>(&conn)?)
+    }
+}
+
+/*************************************/
+/** Get Entry query                 **/
+/*************************************/
+
+#[derive(Clone)]
+pub struct GetEntry {
+    pub person_id: i32,
+    pub entry_id: i32,
+}
+
+impl Query for GetEntry {
+    type Output = Option<Entry>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use crate::schema::drink::dsl::*;
+        use crate::schema::entry;
+        use crate::schema::entry::dsl::*;
+
+        Ok(entry
+            .inner_join(drink)
+            .select(ENTRY_COLUMNS)
+            .filter(
+                entry::person_id
+                    .eq(&self.person_id)
+                    .and(entry::id.eq(&self.entry_id)),
+            )
+            .first::<Entry>(&conn)
+            .optional()?)
+    }
+}
+
+/*************************************/
+/*************************************/
+
+pub struct GetDrink {
+    // @TODO: Associate with person ID?
+    pub name: String,
+    pub abv: Option<Abv>,
+}
+
+impl Query for GetDrink {
+    type Output = Option<Drink>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        self.find(&conn)
+    }
+}
+
+impl GetDrink {
+    /// Find the drink using an existing connection.
+    fn find(&self, conn: &PgConnection) -> Result<Option<Drink>> {
+        use super::schema::drink::dsl::*;
+
+        let min = self.abv.as_ref().map(|abv| abv.min);
+        let max = self.abv.as_ref().map(|abv| abv.max);
+
+        Ok(drink
+            .filter(
+                lower(name)
+                    .eq(&self.name.to_lowercase())
+                    .and(min_abv.eq(&min))
+                    .and(max_abv.eq(&max)),
+            )
+            .first::<Drink>(conn)
+            .optional()?)
+    }
+}
+
+/*************************************/
+/** Get Person By Username query    **/
+/*************************************/
+
+/// Find the person who logs in with the given username.
+pub struct GetPersonByUsername {
+    pub username: String,
+}
+
+impl Query for GetPersonByUsername {
+    type Output = Option<models::Person>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use crate::schema::person::dsl::*;
+
+        Ok(person
+            .filter(username.eq(&self.username))
+            .first::<models::Person>(&conn)
+            .optional()?)
+    }
+}
+
+/*************************************/
+/** Get Similar Drinks query        **/
+/*************************************/
+
+/// Find drinks which may be the same as the given drink, recorded differently;
+/// those whose names begin with the same `name_prefix_len` characters, or whose ABV overlaps.
+pub struct GetSimilarDrinks {
+    pub drink_id: i32,
+    pub name_prefix_len: usize,
+}
+
+impl Query for GetSimilarDrinks {
+    type Output = Vec<Drink>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use super::schema::drink::dsl::*;
+
+        let target = match drink.find(self.drink_id).first::<Drink>(&conn).optional()? {
+            Some(target) => target,
+            None => return Ok(Vec::new()),
+        };
+
+        let pattern = name_prefix_pattern(&target.name, self.name_prefix_len);
+        let mut similar = drink
+            .filter(id.ne(target.id).and(lower(name).like(pattern)))
+            .load::<Drink>(&conn)?;
+
+        // ABV ranges can not be compared in SQL, so check every drink with an ABV.
+        if let Some(target_abv) = Abv::from_approx_f32_pair(target.min_abv, target.max_abv) {
+            let overlapping = drink
+                .filter(id.ne(target.id).and(min_abv.is_not_null()))
+                .load::<Drink>(&conn)?
+                .into_iter()
+                .filter(|d| match Abv::from_approx_f32_pair(d.min_abv, d.max_abv) {
+                    Some(abv) => abv.overlaps(&target_abv),
+                    None => false,
+                });
+
+            for candidate in overlapping {
+                if !similar.iter().any(|d| d.id == candidate.id) {
+                    similar.push(candidate);
+                }
+            }
+        }
+
+        similar.sort_by_key(|d| d.name.to_lowercase());
+        Ok(similar)
+    }
+}
+
+/// Build a `LIKE` pattern matching lowercase names which begin with
+/// the first `prefix_len` characters of `drink_name`.
+fn name_prefix_pattern(drink_name: &str, prefix_len: usize) -> String {
+    let mut pattern: String = drink_name
+        .to_lowercase()
+        .chars()
+        .take(prefix_len)
+        .flat_map(|c| match c {
+            '%' | '_' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect();
+
+    pattern.push('%');
+    pattern
+}
+
+/*************************************/
+/** Get All Drinks query            **/
+/*************************************/
+
+/// Get every drink definition, ordered by name.
+#[derive(Clone)]
+pub struct GetAllDrinks {
+    /// If set, only get drinks which do (`true`) or do not (`false`) have any entries.
+    pub has_entries: Option<bool>,
+}
+
+impl GetAllDrinks {
+    fn query(&self) -> schema::drink::BoxedQuery<'static, diesel::pg::Pg> {
+        use crate::schema::{drink, entry};
+
+        let query = drink::table.order(lower(drink::name).asc()).into_boxed();
+        let used_drinks = entry::table.select(entry::drink_id);
+
+        // `entry.drink_id` can't be null, so `NOT IN` is safe to use here.
+        match self.has_entries {
+            Some(true) => query.filter(drink::id.eq_any(used_drinks)),
+            Some(false) => query.filter(drink::id.ne_all(used_drinks)),
+            None => query,
+        }
+    }
+}
+
+impl Query for GetAllDrinks {
+    type Output = Vec<Drink>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        Ok(self.query().load::<Drink>(&conn)?)
+    }
+}
+
+/// Delete every drink which has no entries, returning how many were deleted.
+pub struct DeleteUnusedDrinks;
+
+impl Query for DeleteUnusedDrinks {
+    type Output = usize;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use crate::schema::{drink, entry};
+
+        let used_drinks = entry::table.select(entry::drink_id);
+
+        Ok(diesel::delete(drink::table.filter(drink::id.ne_all(used_drinks))).execute(&conn)?)
+    }
+}
+
+/*************************************/
+/** Search Drinks query             **/
+/*************************************/
+
+/// Find up to 20 drinks whose names contain `query`, ignoring case, ordered by name.
+#[derive(Clone)]
+pub struct SearchDrinks {
+    pub query: String,
+}
+
+impl SearchDrinks {
+    fn query(&self) -> schema::drink::BoxedQuery<'static, diesel::pg::Pg> {
+        use crate::schema::drink;
+
+        let pattern = format!("%{}", name_prefix_pattern(&self.query, usize::MAX));
+
+        drink::table
+            .filter(lower(drink::name).like(pattern))
+            .order(lower(drink::name).asc())
+            .limit(20)
+            .into_boxed()
+    }
+}
+
+impl Query for SearchDrinks {
+    type Output = Vec<Drink>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        Ok(self.query().load::<Drink>(&conn)?)
+    }
+}
+
+/*************************************/
+/** Merge Drinks query              **/
+/*************************************/
+
+/// The result of merging one drink into another.
+#[derive(Serialize)]
+#[serde(rename = "merge")]
+pub struct DrinkMerge {
+    pub target: Drink,
+
+    /// How many entries were moved from the source drink to the target.
+    pub reassigned_entries: usize,
+}
+
+/// Move every entry of the `source_id` drink to the `target_id` drink, then delete the source.
+///
+/// Returns `None` if either drink does not exist, or the person has no entries of it.
+pub struct MergeDrinks {
+    pub person_id: i32,
+    pub source_id: i32,
+    pub target_id: i32,
+}
+
+impl Query for MergeDrinks {
+    type Output = Option<DrinkMerge>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use crate::schema::{drink, entry};
+
+        let has_entries = |drink_id: i32| -> Result<bool> {
+            Ok(diesel::select(diesel::dsl::exists(
+                entry::table
+                    .filter(entry::person_id.eq(self.person_id))
+                    .filter(entry::drink_id.eq(drink_id)),
+            ))
+            .get_result(&conn)?)
+        };
+
+        conn.transaction::<_, Error, _>(|| {
+            let target = match drink::table
+                .find(self.target_id)
+                .first::<Drink>(&conn)
+                .optional()?
+            {
+                Some(target) => target,
+                None => return Ok(None),
+            };
+
+            if !has_entries(self.source_id)? || !has_entries(self.target_id)? {
+                return Ok(None);
+            }
+
+            // Drinks are shared, so every entry of the source must be moved before it's deleted.
+            let reassigned_entries =
+                diesel::update(entry::table.filter(entry::drink_id.eq(self.source_id)))
+                    .set(entry::drink_id.eq(self.target_id))
+                    .execute(&conn)?;
+
+            diesel::delete(drink::table.find(self.source_id)).execute(&conn)?;
+
+            Ok(Some(DrinkMerge {
+                target,
+                reassigned_entries,
+            }))
+        })
+    }
+}
+
+/*************************************/
+/*************************************/
+
+pub struct CreateDrink {
+    pub name: String,
+    pub abv: Option<Abv>,
+    pub multiplier: f32,
+}
+
+impl CreateDrink {
+    /// Insert this drink using the given connection.
+    fn insert(&self, conn: &PgConnection) -> diesel::QueryResult<Drink> {
+        use super::schema::drink;
+
+        let min = self.abv.as_ref().map(|abv| abv.min);
+        let max = self.abv.as_ref().map(|abv| abv.max);
+
+        let new_drink = super::models::NewDrink {
+            name: self.name.as_str(),
+
+            min_abv: min,
+            max_abv: max,
+
+            multiplier: self.multiplier,
+        };
+
+        diesel::insert_into(drink::table)
+            .values(&new_drink)
+            .get_result(conn)
+    }
+}
+
+impl Query for CreateDrink {
+    type Output = Drink;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        Ok(self.insert(&conn)?)
+    }
+}
+
+/*************************************/
+/** Get or Create Drink query       **/
+/*************************************/
+
+/// Get an existing drink, or create it if no matching drink exists.
+pub struct GetOrCreateDrink {
+    pub name: String,
+    pub abv: Option<Abv>,
+    pub multiplier: f32,
+}
+
+impl Query for GetOrCreateDrink {
+    type Output = Drink;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        let existing = GetDrink {
+            name: self.name.clone(),
+            abv: self.abv.clone(),
+        }
+        .find(&conn)?;
+
+        // Depending on whether the drink exists, either return it or create it.
+        let query: Box<dyn Query<Output = Drink> + Send> = match existing {
+            Some(drink) => Box::new(Existing(drink)),
+            None => Box::new(CreateDrink {
+                name: self.name.clone(),
+                abv: self.abv.clone(),
+                multiplier: self.multiplier,
+            }),
+        };
+
+        query.execute(conn)
+    }
+}
+
+impl GetOrCreateDrink {
+    /// Find or create the drink using an existing connection, ex: within a transaction.
+    fn find_or_create(&self, conn: &PgConnection) -> Result<Drink> {
+        let existing = GetDrink {
+            name: self.name.clone(),
+            abv: self.abv.clone(),
+        }
+        .find(conn)?;
+
+        match existing {
+            Some(drink) => Ok(drink),
+            None => Ok(CreateDrink {
+                name: self.name.clone(),
+                abv: self.abv.clone(),
+                multiplier: self.multiplier,
+            }
+            .insert(conn)?),
+        }
+    }
+}
+
+/// A "query" which simply returns a value which has already been loaded.
+struct Existing<T>(T);
+
+impl<T: Clone + Send> Query for Existing<T> {
+    type Output = T;
+
+    fn execute(&self, _conn: Connection) -> Result<Self::Output> {
+        Ok(self.0.clone())
+    }
+}
+
+/*************************************/
+/*************************************/
+
+pub struct CreateEntry {
+    pub person_id: i32,
+    pub drank_on: NaiveDate,
+    pub time_period: models::TimePeriod,
+    pub context: Vec<String>,
+    pub drink_id: i32,
+    pub quantity: QuantityRange,
+    pub volume: Option<VolumeContext>,
+}
+
+impl CreateEntry {
+    /// Insert this entry for the drink `drink_id`, using the given connection.
+    fn insert(
+        &self,
+        conn: &PgConnection,
+        drink_id: i32,
+    ) -> diesel::QueryResult<models::PlainEntry> {
+        use schema::entry;
+
+        let new_entry = models::NewEntry {
+            person_id: self.person_id,
+            drank_on: &self.drank_on,
+            time_period: &self.time_period,
+            context: &self.context,
+            drink_id,
+            min_quantity: &self.quantity.min,
+            max_quantity: &self.quantity.max,
+            volume: self.volume.as_ref().map(|v| v.volume),
+            volume_ml: self.volume.as_ref().map(|v| v.volume.to_ml()),
+        };
+
+        diesel::insert_into(entry::table)
+            .values(&new_entry)
+            .get_result(conn)
+    }
+}
+
+impl Query for CreateEntry {
+    type Output = models::PlainEntry;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        Ok(self.insert(&conn, self.drink_id)?)
+    }
+}
+
+/// An entry to create with `BulkCreateEntries`, along with its drink,
+/// which is created first if it does not already exist.
+///
+/// The `drink_id` of `entry` is replaced by the ID of `drink`.
+pub struct BulkEntry {
+    pub drink: GetOrCreateDrink,
+    pub entry: CreateEntry,
+}
+
+/// Create several entries, and any new drinks, at once, within a single transaction.
+///
+/// If any drink or entry fails to be inserted, none of them will be saved.
+pub struct BulkCreateEntries {
+    pub entries: Vec<BulkEntry>,
+}
+
+impl Query for BulkCreateEntries {
+    type Output = Vec<models::PlainEntry>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        conn.transaction::<_, Error, _>(|| {
+            self.entries
+                .iter()
+                .enumerate()
+                .map(|(index, BulkEntry { drink, entry })| {
+                    let drink = drink.find_or_create(&conn)?;
+
+                    entry
+                        .insert(&conn, drink.id)
+                        .map_err(|error| Error::BatchEntryError { index, error })
+                })
+                .collect()
+        })
+    }
+}
+
+pub struct DeleteEntry {
+    pub entry: Entry,
+}
+
+impl Query for DeleteEntry {
+    type Output = ();
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        Ok(diesel::delete(&self.entry).execute(&conn).map(|_qs| ())?)
+    }
+}
+
+
+pub struct UpdateEntry {
+    pub entry: Entry,
+
+    /// The person making the change, recorded in the entry's audit log.
+    pub person_id: i32,
+
+    /// `Some(Some(volume))` replaces the entry's volume, `Some(None)`
+    /// clears it, and `None` leaves the volume unchanged.
+    pub volume: Option<Option<VolumeContext>>,
+}
+
+impl UpdateEntry {
+    /// The new values of the `volume` and `volume_ml` columns, if they are being updated.
+    fn volume_columns(&self) -> Option<(Option<LiquidVolume>, Option<LiquidVolume>)> {
+        self.volume.as_ref().map(|context| match context {
+            Some(context) => (Some(context.volume), Some(context.volume.to_ml())),
+            None => (None, None),
+        })
+    }
+
+    /// The fields of `current`, the entry as it is stored, which this update will change,
+    /// as `(field_name, old_value, new_value)`.
+    fn changed_fields(
+        &self,
+        current: &Entry,
+    ) -> Vec<(&'static str, Option<String>, Option<String>)> {
+        let mut changes = vec![
+            (
+                "time_period",
+                Some(current.time.to_string()),
+                Some(self.entry.time.to_string()),
+            ),
+            (
+                "min_quantity",
+                Some(approx_to_string(&current.min_quantity)),
+                Some(approx_to_string(&self.entry.min_quantity)),
+            ),
+            (
+                "max_quantity",
+                Some(approx_to_string(&current.max_quantity)),
+                Some(approx_to_string(&self.entry.max_quantity)),
+            ),
+        ];
+
+        if let Some((new_volume, _)) = self.volume_columns() {
+            changes.push((
+                "volume",
+                current.volume.as_ref().map(volume_to_string),
+                new_volume.as_ref().map(volume_to_string),
+            ));
+        }
+
+        changes
+            .into_iter()
+            .filter(|(_, old_value, new_value)| old_value != new_value)
+            .collect()
+    }
+}
+
+/// Format an approximate value for the audit log, ex: "~2.5".
+fn approx_to_string(value: &ApproxF32) -> String {
+    if value.is_approximate {
+        format!("~{}", value.num)
+    } else {
+        value.num.to_string()
+    }
+}
+
+/// Format a volume for the audit log, ex: "~12 fl oz".
+fn volume_to_string(volume: &LiquidVolume) -> String {
+    format!("{} {}", approx_to_string(&volume.amount), volume.unit)
+}
+
+impl Query for UpdateEntry {
+    type Output = ();
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use schema::entry;
+        use schema::entry::dsl::*;
+
+        conn.transaction::<_, Error, _>(|| {
+            let current = entry
+                .inner_join(schema::drink::table)
+                .select(ENTRY_COLUMNS)
+                .filter(entry::id.eq(self.entry.id))
+                .first::<Entry>(&conn)?;
+
+            diesel::update(entry.find(self.entry.id))
+                .set((
+                    time_period.eq(&self.entry.time),
+                    min_quantity.eq(&self.entry.min_quantity),
+                    max_quantity.eq(&self.entry.max_quantity),
+                ))
+                .execute(&conn)?;
+
+            if let Some((new_volume, new_volume_ml)) = self.volume_columns() {
+                diesel::update(entry.find(self.entry.id))
+                    .set((volume.eq(new_volume), volume_ml.eq(new_volume_ml)))
+                    .execute(&conn)?;
+            }
+
+            let changes: Vec<models::NewAuditLogEntry> = self
+                .changed_fields(&current)
+                .into_iter()
+                .map(
+                    |(field_name, old_value, new_value)| models::NewAuditLogEntry {
+                        entry_id: self.entry.id,
+                        field_name,
+                        old_value,
+                        new_value,
+                        changed_by: self.person_id,
+                    },
+                )
+                .collect();
+
+            if !changes.is_empty() {
+                diesel::insert_into(schema::entry_audit_log::table)
+                    .values(&changes)
+                    .execute(&conn)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// Get the history of changes made to an entry, oldest first.
+pub struct GetEntryAuditLog {
+    pub entry_id: i32,
+    pub person_id: i32,
+}
+
+impl Query for GetEntryAuditLog {
+    type Output = Vec<models::AuditLogEntry>;
+
+    fn execute(&self, conn: Connection) -> Result<Self::Output> {
+        use schema::entry;
+        use schema::entry_audit_log::dsl::*;
+
+        Ok(entry_audit_log
+            .inner_join(entry::table)
+            .select(schema::entry_audit_log::all_columns)
+            .filter(
+                entry_id
+                    .eq(self.entry_id)
+                    .and(entry::person_id.eq(self.person_id)),
+            )
+            .order((changed_at.asc(), id.asc()))
+            .load::<models::AuditLogEntry>(&conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Statically check that `query` can be passed to `execute`.
+    fn assert_executable<T: Query + Send + 'static>(_query: &T) {}
+
+    /// Requires a database with a person with ID 1; run with `DATABASE_URL` set,
+    /// using `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_bulk_create_entries_rolls_back() {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set!");
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(r2d2::ConnectionManager::new(database_url))
+            .unwrap();
+
+        let count_entries = || -> i64 {
+            schema::entry::table
+                .count()
+                .get_result(&pool.get().unwrap())
+                .unwrap()
+        };
+        let before = count_entries();
+
+        let abv = Abv::from_str("5%").unwrap();
+        let bulk_entry = |person_id| BulkEntry {
+            drink: GetOrCreateDrink {
+                name: "bulk create rollback test".into(),
+                abv: abv.clone(),
+                multiplier: 1.0,
+            },
+            entry: CreateEntry {
+                person_id,
+                drank_on: NaiveDate::from_ymd(2020, 5, 1),
+                time_period: TimePeriod::Evening,
+                context: vec!["test".into()],
+                drink_id: 0,
+                quantity: QuantityRange::from_str("1").unwrap(),
+                volume: None,
+            },
+        };
+
+        // There is no person with a negative ID, so the second entry can't be saved.
+        let result = BulkCreateEntries {
+            entries: vec![bulk_entry(1), bulk_entry(-1)],
+        }
+        .execute(pool.get().unwrap());
+
+        match result {
+            Err(Error::BatchEntryError { index, .. }) => assert_eq!(1, index),
+            _ => panic!("Expected the second entry to fail!"),
+        }
+
+        // Neither the first entry, nor the new drink, should have been saved.
+        assert_eq!(before, count_entries());
+        let drink = GetDrink {
+            name: "bulk create rollback test".into(),
+            abv,
+        }
+        .find(&pool.get().unwrap())
+        .unwrap();
+        assert!(drink.is_none());
+    }
+
+    #[test]
+    fn test_boxed_query() {
+        let queries: Vec<Box<dyn Query<Output = Option<Drink>> + Send>> = vec![
+            Box::new(GetDrink {
+                name: "stout".into(),
+                abv: None,
+            }),
+            Box::new(Existing(None)),
+        ];
+
+        for query in queries.iter() {
+            assert_executable(query);
+        }
+
+        assert_executable(&GetOrCreateDrink {
+            name: "stout".into(),
+            abv: None,
+            multiplier: 1.0,
+        });
+    }
+
+    #[test]
+    fn test_get_drinks_date_range_validation() {
+        let get_drinks = |start: NaiveDate, end: NaiveDate| GetDrinks {
+            person_id: 1,
+            date_range: Some((start, end)),
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        };
+        let may_4 = NaiveDate::from_ymd(2020, 5, 4);
+        let may_10 = NaiveDate::from_ymd(2020, 5, 10);
+
+        assert!(get_drinks(may_4, may_10).validate().is_ok());
+        assert!(get_drinks(may_4, may_4).validate().is_ok());
+
+        match get_drinks(may_10, may_4).validate() {
+            Err(Error::InvalidDateRange { start, end }) => {
+                assert_eq!(may_10, start);
+                assert_eq!(may_4, end);
+            }
+            _ => panic!("Expected an invalid date range error!"),
+        }
+
+        let no_range = GetDrinks {
+            person_id: 1,
+            date_range: None,
+            drink_id: None,
+            time_period: None,
+            context_contains: None,
+            limit: None,
+            offset: None,
+        };
+        assert!(no_range.validate().is_ok());
+    }
+
+    #[test]
+    fn test_name_prefix_pattern() {
+        assert_eq!("guinn%", name_prefix_pattern("Guinness", 5));
+        assert_eq!("guinness%", name_prefix_pattern("Guinness", 20));
+        assert_eq!("%", name_prefix_pattern("Guinness", 0));
+        assert_eq!("caf\\_%", name_prefix_pattern("Caf_é", 4));
+        assert_eq!("café%", name_prefix_pattern("Café Patrón", 4));
+        assert_eq!("pub\\_crawl%", name_prefix_pattern("Pub_Crawl", usize::MAX));
+
+        // A dissimilar name should not match the pattern.
+        let pattern = name_prefix_pattern("Guinness Draught", 5);
+        assert!("guinness".starts_with(pattern.trim_end_matches('%')));
+        assert!(!"stella artois".starts_with(pattern.trim_end_matches('%')));
+    }
+
+    #[test]
+    fn test_approx_bound_sql() {
+        assert_eq!(
+            "(e.min_quantity).val * (CASE WHEN (e.min_quantity).is_approximate THEN 0.9 ELSE 1 END)",
+            approx_bound_sql("e.min_quantity", false)
+        );
+        assert_eq!(
+            "(d.max_abv).val * (CASE WHEN (d.max_abv).is_approximate THEN 1.1 ELSE 1 END)",
+            approx_bound_sql("d.max_abv", true)
+        );
+    }
+
+    #[test]
+    fn test_drinks_sql() {
+        let min_drinks = drinks_sql(false, "$2");
+        assert!(min_drinks.contains(&format!(
+            "THEN {} * d.multiplier",
+            approx_bound_sql("e.min_quantity", false)
+        )));
+        assert!(min_drinks.contains(&approx_bound_sql("d.min_abv", false)));
+        assert!(min_drinks.contains("/ $2"));
+        assert!(!min_drinks.contains("max_"));
+
+        let max_drinks = drinks_sql(true, "$4");
+        assert!(max_drinks.contains(&approx_bound_sql("e.max_quantity", true)));
+        assert!(max_drinks.contains(&approx_bound_sql("(e.volume_ml).volume", true)));
+        assert!(max_drinks.contains("/ $4"));
+    }
+
+    #[test]
+    fn test_monthly_row_serialization() {
+        let row = MonthlyRow {
+            month: NaiveDate::from_ymd(2020, 5, 1),
+            entry_count: 12,
+            min_drinks: 10.5,
+            max_drinks: 14.0,
+            distinct_drinks: 4,
+        };
+
+        assert_eq!(
+            serde_json::json!({
+                "month": "2020-05-01",
+                "entry_count": 12,
+                "min_drinks": 10.5,
+                "max_drinks": 14.0,
+                "distinct_drinks": 4,
+            }),
+            serde_json::to_value(&row).unwrap()
+        );
+    }
+
+    fn make_volume_entry(volume: Option<LiquidVolume>) -> Entry {
+        Entry {
+            id: 1,
+            drank_on: NaiveDate::from_ymd(2020, 5, 4),
+            time: TimePeriod::Evening,
+            context: Vec::new(),
+            drink_id: 1,
+            name: "beer".into(),
+            min_abv: None,
+            max_abv: None,
+            multiplier: 1.0,
+            min_quantity: ApproxF32::new(1.0, false),
+            max_quantity: ApproxF32::new(1.0, false),
+            volume,
+            volume_ml: volume.map(|v| v.to_ml()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_entry_volume_in_unit() {
+        let volume = |num, unit| LiquidVolume {
+            amount: ApproxF32::new(num, true),
+            unit,
+        };
+
+        let entry = make_volume_entry(Some(volume(355.0, VolumeUnit::mL)));
+        let fl_oz = entry.volume_fl_oz().unwrap();
+        assert_eq!(VolumeUnit::FlOz, fl_oz.unit);
+        assert!((fl_oz.amount.num - 12.0).abs() < 0.01);
+        assert!(fl_oz.amount.is_approximate);
+
+        let entry = make_volume_entry(Some(volume(12.0, VolumeUnit::FlOz)));
+        let ml = entry.volume_ml().unwrap();
+        assert_eq!(VolumeUnit::mL, ml.unit);
+        assert!((ml.amount.num - 354.88).abs() < 0.01);
+
+        let cl = entry.volume_in_unit(VolumeUnit::cL).unwrap();
+        assert_eq!(VolumeUnit::cL, cl.unit);
+        assert!((cl.amount.num - 35.488).abs() < 0.001);
+
+        let entry = make_volume_entry(None);
+        assert!(entry.volume_fl_oz().is_none());
+        assert!(entry.volume_ml().is_none());
+    }
+
+    #[test]
+    fn test_update_entry_volume_columns() {
+        use crate::models::{ApproxF32, VolumeUnit};
+
+        let update = |volume: Option<Option<VolumeContext>>| UpdateEntry {
+            person_id: 1,
+            entry: Entry {
+                id: 1,
+                drank_on: NaiveDate::from_ymd(2020, 5, 4),
+                time: TimePeriod::Evening,
+                context: Vec::new(),
+                drink_id: 1,
+                name: "beer".into(),
+                min_abv: None,
+                max_abv: None,
+                multiplier: 1.0,
+                min_quantity: ApproxF32::new(1.0, false),
+                max_quantity: ApproxF32::new(1.0, false),
+                volume: None,
+                volume_ml: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            volume,
+        };
+
+        assert!(update(None).volume_columns().is_none());
+
+        let (volume, volume_ml) = update(Some(None)).volume_columns().unwrap();
+        assert!(volume.is_none());
+        assert!(volume_ml.is_none());
+
+        let twelve_oz = LiquidVolume {
+            amount: ApproxF32::new(12.0, false),
+            unit: VolumeUnit::FlOz,
+        };
+        let context = VolumeContext {
+            volume: twelve_oz,
+            original_unit: None,
+        };
+        let (volume, volume_ml) = update(Some(Some(context))).volume_columns().unwrap();
+        let volume_ml = volume_ml.unwrap();
+
+        assert_eq!(Some(twelve_oz), volume);
+        assert_eq!(VolumeUnit::mL, volume_ml.unit);
+        assert!((volume_ml.amount.num - 354.882).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_update_entry_changed_fields() {
+        let current = make_volume_entry(None);
+        let mut updated = current.clone();
+        updated.time = TimePeriod::Night;
+        updated.max_quantity = ApproxF32::new(2.0, true);
+
+        let update = |volume| UpdateEntry {
+            entry: updated.clone(),
+            person_id: 1,
+            volume,
+        };
+
+        assert_eq!(
+            vec![
+                (
+                    "time_period",
+                    Some("evening".to_string()),
+                    Some("night".to_string())
+                ),
+                (
+                    "max_quantity",
+                    Some("1".to_string()),
+                    Some("~2".to_string())
+                ),
+            ],
+            update(None).changed_fields(&current)
+        );
+
+        let volume = VolumeContext {
+            volume: LiquidVolume {
+                amount: ApproxF32::new(12.0, false),
+                unit: VolumeUnit::FlOz,
+            },
+            original_unit: None,
+        };
+        let changes = update(Some(Some(volume))).changed_fields(&current);
+        assert_eq!(("volume", None, Some("12 fl oz".to_string())), changes[2]);
+
+        // Clearing a volume which was never set is not a change.
+        assert_eq!(2, update(Some(None)).changed_fields(&current).len());
+
+        let unchanged = UpdateEntry {
+            entry: current.clone(),
+            person_id: 1,
+            volume: None,
+        };
+        assert!(unchanged.changed_fields(&current).is_empty());
+    }
+
+    #[test]
+    fn test_get_all_drinks_has_entries() {
+        let sql = |has_entries| {
+            diesel::debug_query::<diesel::pg::Pg, _>(&GetAllDrinks { has_entries }.query())
+                .to_string()
+        };
+
+        let used_drinks = "(SELECT \"entry\".\"drink_id\" FROM \"entry\")";
+
+        let with_entries = sql(Some(true));
+        assert!(with_entries.contains(&format!("\"drink\".\"id\" IN {}", used_drinks)));
+
+        let without_entries = sql(Some(false));
+        assert!(without_entries.contains(&format!("\"drink\".\"id\" NOT IN {}", used_drinks)));
+
+        let all = sql(None);
+        assert!(!all.contains("WHERE"));
+        assert!(all.contains("ORDER BY lower(\"drink\".\"name\") ASC"));
+    }
+
+    #[test]
+    fn test_get_drinks_filtered() {
+        let query = GetDrinks {
+            person_id: 1,
+            date_range: Some((
+                NaiveDate::from_ymd(2020, 1, 1),
+                NaiveDate::from_ymd(2020, 1, 31),
+            )),
+            drink_id: Some(2),
+            time_period: Some(TimePeriod::Evening),
+            context_contains: Some("Pub".into()),
+            limit: Some(10),
+            offset: Some(20),
+        };
+
+        let sql = diesel::debug_query::<diesel::pg::Pg, _>(&query.filtered()).to_string();
+        assert!(sql.contains("\"entry\".\"person_id\" = $1"));
+        assert!(sql.contains("\"entry\".\"drank_on\" >= $2"));
+        assert!(sql.contains("\"entry\".\"drank_on\" <= $3"));
+        assert!(sql.contains("\"entry\".\"drink_id\" = $4"));
+        assert!(sql.contains("\"entry\".\"time_period\" = $5"));
+        assert!(sql.contains("\"entry\".\"context\" @> $6"));
+
+        // The limit and offset only apply to the entries, not to the count of every entry.
+        assert!(!sql.contains("LIMIT"));
+        assert!(!sql.contains("OFFSET"));
+    }
+
+    #[test]
+    fn test_search_drinks() {
+        let sql = |query: &str| {
+            diesel::debug_query::<diesel::pg::Pg, _>(
+                &SearchDrinks {
+                    query: query.into(),
+                }
+                .query(),
+            )
+            .to_string()
+        };
+
+        let search = sql("Pale Ale");
+        assert!(search.contains("WHERE lower(\"drink\".\"name\") LIKE $1"));
+        assert!(search.contains("LIMIT $2"));
+        assert!(search.contains("\"%pale ale%\", 20"));
+
+        // Wildcards in the query are matched literally.
+        assert!(sql("100%").contains("\"%100\\\\%%\""));
     }
 }
@@ -1,5 +1,9 @@
+use crate::api::ApiResponse;
 use actix_web::error::ResponseError;
+use actix_web::http::StatusCode;
 use actix_web::Error as ActixError;
+use actix_web::HttpResponse;
+use chrono::NaiveDate;
 use diesel::r2d2;
 use diesel::result::Error as DieselError;
 use futures::channel::oneshot::Canceled as FutureCanceled;
@@ -20,6 +24,33 @@ pub enum Error {
     FutureCanceled(FutureCanceled),
 
     EntryInputError(String),
+
+    ValidationError(String),
+
+    IoError(std::io::Error),
+
+    CsvError(csv::Error),
+
+    ZipError(zip::result::ZipError),
+
+    #[display(fmt = "Failed to create entry {}: {}", index, error)]
+    BatchEntryError {
+        index: usize,
+        error: DieselError,
+    },
+
+    #[display(fmt = "Invalid date range: {} is after {}", start, end)]
+    InvalidDateRange {
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+
+    #[display(fmt = "Invalid credentials")]
+    Unauthorized,
+
+    JwtError(jsonwebtoken::errors::Error),
+
+    BcryptError(bcrypt::BcryptError),
 }
 
 impl std::error::Error for Error {
@@ -31,11 +62,44 @@ impl std::error::Error for Error {
             Self::FutureCanceled(e) => Some(e),
             Self::SessionNotFound => None,
             Self::EntryInputError(_) => None,
+            Self::ValidationError(_) => None,
+            Self::IoError(e) => Some(e),
+            Self::CsvError(e) => Some(e),
+            Self::ZipError(e) => Some(e),
+            Self::BatchEntryError { error, .. } => Some(error),
+            Self::InvalidDateRange { .. } => None,
+            Self::Unauthorized => None,
+            Self::JwtError(e) => Some(e),
+            Self::BcryptError(e) => Some(e),
         }
     }
 }
 
-impl ResponseError for Error {}
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidDateRange { .. } => StatusCode::BAD_REQUEST,
+            Self::EntryInputError(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            Self::InvalidDateRange { .. } | Self::EntryInputError(_) => {
+                HttpResponse::BadRequest().json(ApiResponse::error_message(self.to_string()))
+            }
+            Self::Unauthorized => {
+                HttpResponse::Unauthorized().json(ApiResponse::error_message(self.to_string()))
+            }
+            Self::ValidationError(_) => HttpResponse::UnprocessableEntity()
+                .json(ApiResponse::error_message(self.to_string())),
+            _ => HttpResponse::new(self.status_code()),
+        }
+    }
+}
 
 impl From<DieselError> for Error {
     fn from(e: DieselError) -> Error {
@@ -60,3 +124,82 @@ impl From<FutureCanceled> for Error {
         Error::FutureCanceled(e)
     }
 }
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::IoError(e)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Error {
+        Error::CsvError(e)
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Error {
+        Error::ZipError(e)
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for Error {
+    fn from(e: jsonwebtoken::errors::Error) -> Error {
+        Error::JwtError(e)
+    }
+}
+
+impl From<bcrypt::BcryptError> for Error {
+    fn from(e: bcrypt::BcryptError) -> Error {
+        Error::BcryptError(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_date_range_is_bad_request() {
+        let error = Error::InvalidDateRange {
+            start: NaiveDate::from_ymd(2020, 5, 10),
+            end: NaiveDate::from_ymd(2020, 5, 4),
+        };
+
+        assert_eq!(StatusCode::BAD_REQUEST, error.status_code());
+        assert_eq!(StatusCode::BAD_REQUEST, error.error_response().status());
+        assert_eq!(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Error::SessionNotFound.status_code()
+        );
+    }
+
+    #[test]
+    fn test_unauthorized_status() {
+        assert_eq!(StatusCode::UNAUTHORIZED, Error::Unauthorized.status_code());
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            Error::Unauthorized.error_response().status()
+        );
+    }
+
+    #[test]
+    fn test_entry_input_error_is_bad_request() {
+        let error = Error::EntryInputError("Invalid ABV value!".into());
+
+        assert_eq!(StatusCode::BAD_REQUEST, error.status_code());
+        assert_eq!(StatusCode::BAD_REQUEST, error.error_response().status());
+    }
+
+    #[test]
+    fn test_validation_error_status() {
+        let error = Error::ValidationError("min ABV exceeds max".into());
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, error.status_code());
+        assert_eq!(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            error.error_response().status()
+        );
+        assert_eq!("min ABV exceeds max", error.to_string());
+    }
+}
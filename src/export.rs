@@ -0,0 +1,412 @@
+use crate::db::Entry;
+use crate::import::{QuantityRange, VolumeContext};
+use crate::reports::DrinkingSession;
+use crate::Result;
+use actix_web::web::Bytes;
+use chrono::{DateTime, Datelike, Utc};
+use futures::Stream;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A comment written at the top of every CSV export, naming the columns in the same layout
+/// read by the import binary, so that an export can be imported again.
+static CSV_HEADER: &str = "# (date; time_period; context), quantity, name, abv, volume\n";
+
+/// Write the given `entries` to `writer` as CSV.
+pub fn write_csv<'a, W: Write>(
+    entries: impl IntoIterator<Item = &'a Entry>,
+    mut writer: W,
+) -> Result<()> {
+    writer.write_all(CSV_HEADER.as_bytes())?;
+
+    let mut writer = csv::Writer::from_writer(writer);
+    for entry in entries {
+        writer.write_record(&csv_record(entry))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Format an entry as the fields of a single CSV row.
+///
+/// The date includes the year, so the year does not need to be inferred on import.
+fn csv_record(entry: &Entry) -> [String; 5] {
+    let quantity = QuantityRange {
+        min: entry.min_quantity,
+        max: entry.max_quantity,
+    };
+
+    // The stored unit is the one parsed on import, with any ambiguous pint already resolved.
+    let volume = entry.volume.map(|volume| VolumeContext {
+        volume,
+        original_unit: Some(volume.unit),
+    });
+
+    let day = entry.drank_on.format("%-d %b %Y").to_string();
+    let mut date = vec![day.to_lowercase(), entry.time.to_string()];
+    date.extend(entry.context.iter().cloned());
+
+    [
+        format!("({})", date.join("; ")),
+        quantity.to_string(),
+        entry.name.clone(),
+        entry.abv().map(|abv| abv.to_string()).unwrap_or_default(),
+        volume
+            .map(|volume| volume.to_csv_string())
+            .unwrap_or_default(),
+    ]
+}
+
+/// Group the given `entries` by the year in which they were drank.
+pub fn group_by_year(entries: &[Entry]) -> BTreeMap<i32, Vec<&Entry>> {
+    let mut years: BTreeMap<i32, Vec<&Entry>> = BTreeMap::new();
+
+    for entry in entries {
+        years.entry(entry.drank_on.year()).or_default().push(entry);
+    }
+
+    years
+}
+
+/// Create a ZIP archive containing one CSV file per year; ex: `drinks-2020.csv`.
+pub fn write_zip(entries: &[Entry]) -> Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (year, entries) in group_by_year(entries) {
+        let mut csv = Vec::new();
+        write_csv(entries, &mut csv)?;
+
+        zip.start_file(format!("drinks-{}.csv", year), Default::default())?;
+        zip.write_all(&csv)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Write the given drinking `sessions` as an iCalendar feed, with one all-day event each.
+///
+/// `now` is used as the time at which every event was created.
+pub fn write_ical(sessions: &[DrinkingSession], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//drink-list//drinks export//EN".to_string(),
+    ];
+
+    for session in sessions {
+        let drinks: Vec<String> = session
+            .entries
+            .iter()
+            .map(|entry| {
+                let quantity = QuantityRange {
+                    min: entry.min_quantity,
+                    max: entry.max_quantity,
+                };
+
+                match entry.abv() {
+                    Some(abv) => format!("{} x {} ({})", quantity, entry.name, abv),
+                    None => format!("{} x {}", quantity, entry.name),
+                }
+            })
+            .collect();
+
+        let summary = format!(
+            "{} drinks ({:.1}-{:.1} units)",
+            session.entries.len(),
+            session.total.min_drinks,
+            session.total.max_drinks
+        );
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-{}@drink-list",
+            session.date.format("%Y%m%d"),
+            session.time_period
+        ));
+        lines.push(format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!(
+            "DTSTART;VALUE=DATE:{}",
+            session.date.format("%Y%m%d")
+        ));
+        lines.push(format!("SUMMARY:{}", ical_escape(&summary)));
+        lines.push(format!("DESCRIPTION:{}", ical_escape(&drinks.join("\n"))));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| ical_fold(line))
+        .map(|line| line + "\r\n")
+        .collect()
+}
+
+/// Escape the characters with special meaning in an iCalendar text value.
+fn ical_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line so that no line is longer than 75 bytes,
+/// continuing it on the following lines after a single space.
+fn ical_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+/// A stream of newline-delimited JSON, serializing one item per line as it is read.
+pub struct JsonExportStream<T> {
+    items: std::vec::IntoIter<T>,
+}
+
+impl<T> JsonExportStream<T> {
+    pub fn new(items: Vec<T>) -> JsonExportStream<T> {
+        JsonExportStream {
+            items: items.into_iter(),
+        }
+    }
+}
+
+impl<T: Serialize + Unpin> Stream for JsonExportStream<T> {
+    type Item = std::result::Result<Bytes, actix_web::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let line = self.items.next().map(|item| -> Self::Item {
+            let mut line = serde_json::to_vec(&item)?;
+            line.push(b'\n');
+            Ok(Bytes::from(line))
+        });
+
+        Poll::Ready(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ApproxF32, TimePeriod};
+    use chrono::{NaiveDate, Utc};
+    use std::io::Read;
+
+    fn make_entry(drank_on: NaiveDate, name: &str) -> Entry {
+        Entry {
+            id: 1,
+            drank_on,
+            time: TimePeriod::Evening,
+            context: vec!["bar".into()],
+            drink_id: 1,
+            name: name.into(),
+            min_abv: Some(ApproxF32::new(5.0, false)),
+            max_abv: Some(ApproxF32::new(5.0, false)),
+            multiplier: 1.0,
+            min_quantity: ApproxF32::new(2.0, false),
+            max_quantity: ApproxF32::new(2.0, false),
+            volume: None,
+            volume_ml: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let entries = vec![make_entry(NaiveDate::from_ymd(2020, 5, 1), "pale ale")];
+
+        let mut csv = Vec::new();
+        write_csv(&entries, &mut csv).unwrap();
+
+        assert_eq!(
+            "# (date; time_period; context), quantity, name, abv, volume\n\
+             (1 may 2020; evening; bar),2.00,pale ale,5.0%,\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_csv_round_trip() {
+        use crate::import::{Abv, DateContext, RawEntry};
+        use crate::models::{LiquidVolume, VolumeUnit};
+
+        let mut entries = vec![
+            make_entry(NaiveDate::from_ymd(2019, 12, 31), "pale ale, cask"),
+            make_entry(NaiveDate::from_ymd(2020, 1, 2), "stout"),
+        ];
+        entries[0].min_quantity = ApproxF32::new(1.0, false);
+        entries[0].max_quantity = ApproxF32::new(2.5, true);
+        entries[0].volume = Some(LiquidVolume {
+            amount: ApproxF32::new(12.0, false),
+            unit: VolumeUnit::FlOz,
+        });
+        entries[1].time = TimePeriod::Afternoon;
+        entries[1].context = vec!["pub".into(), "birthday".into()];
+        entries[1].min_abv = None;
+        entries[1].max_abv = None;
+        entries[1].volume = Some(LiquidVolume {
+            amount: ApproxF32::new(1.0, false),
+            unit: VolumeUnit::UkPint,
+        });
+
+        let mut csv = Vec::new();
+        write_csv(&entries, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.contains(",12.00 oz\n"));
+
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .comment(Some(b'#'))
+            .from_reader(csv.as_bytes());
+        let records: Vec<RawEntry> = reader
+            .records()
+            .map(|record| RawEntry::from_csv_record(record.unwrap()).unwrap())
+            .collect();
+        assert_eq!(entries.len(), records.len());
+
+        // The previous date is in a different year, so the years must come from the export.
+        let previous = DateContext {
+            date: NaiveDate::from_ymd(2018, 1, 1),
+            time: TimePeriod::Evening,
+            context: vec![],
+        };
+
+        for (entry, record) in entries.iter().zip(records.iter()) {
+            let date = DateContext::from_entry(record, &previous);
+            assert_eq!(entry.drank_on, date.date);
+            assert_eq!(entry.time, date.time);
+            assert_eq!(entry.context, date.context);
+
+            let quantity = QuantityRange::from_entry(record).unwrap();
+            assert_eq!(entry.min_quantity, quantity.min);
+            assert_eq!(entry.max_quantity, quantity.max);
+
+            assert_eq!(Some(&entry.name), record.name.as_ref());
+            assert_eq!(entry.abv(), Abv::from_entry(record).unwrap());
+
+            let volume = VolumeContext::from_entry_with_context(record, &date.context).unwrap();
+            let expected = entry.volume.unwrap();
+            assert_eq!(expected.amount, volume.volume.amount);
+            assert_eq!(expected.unit, volume.volume.unit);
+        }
+    }
+
+    #[test]
+    fn test_write_zip() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 1, 2), "stout"),
+            make_entry(NaiveDate::from_ymd(2019, 12, 31), "champagne"),
+            make_entry(NaiveDate::from_ymd(2019, 6, 1), "lager"),
+        ];
+
+        let zip = write_zip(&entries).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(zip)).unwrap();
+
+        assert_eq!(2, archive.len());
+
+        let mut contents = String::new();
+        let mut file = archive.by_name("drinks-2019.csv").unwrap();
+        file.read_to_string(&mut contents).unwrap();
+
+        assert!(contents.contains("champagne"));
+        assert!(contents.contains("lager"));
+        assert!(!contents.contains("stout"));
+        drop(file);
+
+        assert!(archive.by_name("drinks-2020.csv").is_ok());
+    }
+
+    #[test]
+    fn test_json_export_stream() {
+        use futures::StreamExt;
+
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 1, 2), "stout"),
+            make_entry(NaiveDate::from_ymd(2019, 12, 31), "champagne"),
+            make_entry(NaiveDate::from_ymd(2019, 6, 1), "lager"),
+        ];
+
+        let lines: Vec<Bytes> =
+            futures::executor::block_on(JsonExportStream::new(entries).collect::<Vec<_>>())
+                .into_iter()
+                .map(|line| line.unwrap())
+                .collect();
+
+        assert_eq!(3, lines.len());
+        for line in lines.iter() {
+            assert_eq!(Some(&b'\n'), line.last());
+            let json: serde_json::Value = serde_json::from_slice(line).unwrap();
+            assert!(json.get("name").is_some());
+        }
+
+        let first: serde_json::Value = serde_json::from_slice(&lines[0]).unwrap();
+        assert_eq!("stout", first["name"]);
+    }
+
+    #[test]
+    fn test_write_ical() {
+        use crate::reports::{drinking_sessions, DrinkStandard};
+        use chrono::TimeZone;
+
+        let mut entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 5, 1), "pale ale"),
+            make_entry(NaiveDate::from_ymd(2020, 5, 1), "stout; extra"),
+            make_entry(NaiveDate::from_ymd(2020, 5, 2), "cider"),
+        ];
+        entries[1].min_abv = None;
+        entries[1].max_abv = None;
+
+        let now = Utc.ymd(2020, 5, 3).and_hms(12, 30, 0);
+        let ical = write_ical(&drinking_sessions(entries, &DrinkStandard::default()), now);
+
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(2, ical.matches("BEGIN:VEVENT\r\n").count());
+        assert!(ical.contains("\r\nUID:20200501-evening@drink-list\r\n"));
+        assert!(ical.contains("\r\nDTSTAMP:20200503T123000Z\r\n"));
+        assert!(ical.contains("\r\nDTSTART;VALUE=DATE:20200501\r\n"));
+        assert!(ical.contains("\r\nSUMMARY:2 drinks (4.0-4.0 units)\r\n"));
+        let description = "DESCRIPTION:2.00 x pale ale (5.0%)\\n2.00 x stout\\; extra";
+        assert!(ical.contains(&format!("\r\n{}\r\n", description)));
+
+        // The most recent session comes first.
+        assert!(
+            ical.find("DTSTART;VALUE=DATE:20200502") < ical.find("DTSTART;VALUE=DATE:20200501")
+        );
+    }
+
+    #[test]
+    fn test_ical_fold() {
+        assert_eq!("short", ical_fold("short"));
+
+        let long = "x".repeat(80);
+        let folded = ical_fold(&long);
+        assert_eq!(format!("{}\r\n {}", "x".repeat(75), "x".repeat(5)), folded);
+
+        // Multi-byte characters are never split across lines.
+        let folded = ical_fold(&"é".repeat(40));
+        assert!(folded.lines().all(|line| line.len() <= 75));
+        assert_eq!("é".repeat(40), folded.replace("\r\n ", ""));
+    }
+}
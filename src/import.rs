@@ -1,4 +1,6 @@
+use crate::db;
 use crate::error::Error;
+use crate::import_config::ImportConfig;
 use crate::models::{ApproxF32, LiquidVolume, TimePeriod, VolumeUnit};
 use crate::Result;
 use chrono::prelude::*;
@@ -9,6 +11,8 @@ use std::hash::{Hash, Hasher};
 use uom::si::f32::*;
 use uom::si::volume::{centiliter, fluid_ounce, liter, milliliter};
 
+pub mod error;
+
 /// Represents the components of an entry line
 pub struct RawEntry {
     pub date: Option<String>,
@@ -19,7 +23,19 @@ pub struct RawEntry {
 }
 
 impl RawEntry {
+    /// Check if a line holds no entry, because it is blank or a comment starting with `#`,
+    /// ex: `# New Year's Eve`.
+    pub fn is_ignored_line(line: &str) -> bool {
+        let line = line.trim();
+
+        line.is_empty() || line.starts_with('#')
+    }
+
     pub fn from_line(line: &str) -> Option<RawEntry> {
+        if Self::is_ignored_line(line) {
+            return None;
+        }
+
         lazy_static! {
             static ref RE: Regex = Regex::new("(?:\\((?P<date>.*?)\\))?,?(?P<quantity>.*?),(?P<name>.*?)(?:,(?P<abv>.*?)(?:,(?P<volume>.*?))?)?$").unwrap();
         }
@@ -41,9 +57,121 @@ impl RawEntry {
             volume: cap_str("volume"),
         })
     }
+
+    /// Build an entry from a CSV record, mapping each field by its position
+    /// rather than by regex.
+    ///
+    /// The first field may begin with a parenthesized date, which can itself contain
+    /// commas, and may be followed directly by the quantity (ex: `(1 oct, bar) 2`).
+    pub fn from_csv_record(record: csv::StringRecord) -> Option<RawEntry> {
+        let mut raw_fields = record.iter();
+
+        let mut first = raw_fields.next()?.trim_start().to_string();
+        let mut date = None;
+
+        if first.starts_with('(') {
+            // Keep consuming fields until the date's closing parenthesis is found.
+            // The raw fields are used, so the spacing after each comma in the date is kept.
+            while !first.contains(')') {
+                first.push(',');
+                first.push_str(raw_fields.next()?);
+            }
+
+            let close = first.find(')').unwrap();
+            date = Some(first[1..close].trim().to_string());
+            first = first[close + 1..].to_string();
+        }
+
+        let first = first.trim().to_string();
+        let mut fields = raw_fields.map(|f| f.trim());
+
+        // If nothing followed the date, then the quantity is in the next field.
+        let quantity = match first.is_empty() {
+            true => fields.next()?.to_string(),
+            false => first,
+        };
+
+        let name = fields.next()?.to_string();
+
+        Some(RawEntry {
+            date,
+            quantity: Some(quantity),
+            name: Some(name),
+            abv: fields.next().map(String::from),
+            volume: fields.next().map(String::from),
+        })
+    }
+
+    /// Build an entry from a single line of newline-delimited JSON.
+    ///
+    /// Ex: `{"date": "1 oct; bar", "quantity": "2", "name": "IPA", "abv": "6.5%"}`
+    pub fn from_json_str(s: &str) -> Option<RawEntry> {
+        use serde_json::Value;
+
+        let value = serde_json::from_str::<Value>(s).ok()?;
+        let object = value.as_object()?;
+
+        // Helper function to retrieve fields by name; numbers are accepted as well as strings.
+        let field = |name: &str| match object.get(name) {
+            Some(Value::String(s)) => Some(s.trim().to_string()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+
+        Some(RawEntry {
+            date: field("date"),
+            quantity: Some(field("quantity")?),
+            name: Some(field("name")?),
+            abv: field("abv"),
+            volume: field("volume"),
+        })
+    }
+}
+
+/// Parse a strings like "2", "1.5", "~3", etc, and return a tuple
+/// indicating whether the value is approximate, and what the base numeric value is.
+///
+/// # Examples
+///
+/// ```
+/// use drink_list::import::parse_approx_value;
+///
+/// assert_eq!((false, 1.5), parse_approx_value("1.5"));
+/// assert_eq!((true, 3.0), parse_approx_value("~3"));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `value` is not a number, with or without a leading `~`.
+pub fn parse_approx_value(value: &str) -> (bool, f32) {
+    use std::str::FromStr;
+
+    let is_approximate = value.starts_with("~");
+    let value = f32::from_str(value.trim_start_matches("~"))
+        .expect(&format!("Failed to parse number, '{}'!", value));
+
+    (is_approximate, value)
 }
 
-#[derive(Clone, Debug)]
+/// Split a string of context tags, separated by commas or semicolons,
+/// into trimmed, lowercase tags, ignoring any empty tags.
+pub fn parse_context_tags(context: &str) -> Vec<String> {
+    context
+        .split(|c| c == ',' || c == ';')
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Find the indices of `dates` (in import order) at which the year changes
+/// from that of the previous date, to help verify that years were inferred correctly.
+pub fn detect_year_rollover(dates: &[NaiveDate]) -> Vec<usize> {
+    (1..dates.len())
+        .filter(|&i| dates[i].year() != dates[i - 1].year())
+        .collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct DateContext {
     pub date: NaiveDate,
     pub time: TimePeriod,
@@ -51,10 +179,21 @@ pub struct DateContext {
 }
 
 impl DateContext {
+    /// Get the date and time to carry forward to the next entry.
+    ///
+    /// Context is specific to each entry, so it is not carried forward.
+    pub fn into_previous(&self) -> DateContext {
+        DateContext {
+            date: self.date,
+            time: self.time,
+            context: Vec::new(),
+        }
+    }
+
     pub fn from_entry(entry: &RawEntry, previous: &DateContext) -> DateContext {
         lazy_static! {
             static ref RE: Regex = Regex::new(
-                r#"^(?P<day>(?:\d{1,2}\s\w{3})|(?:\w{3}\s\d{1,2}))?[,; ]*(?:(?P<context2>[^\r\n;,]*?)[;,]?)?(?:(?P<context1>[^\r\n;,]*?)[;,]?)?$"#
+                r#"^(?:(?P<day>(?:\d{1,2}\s\w{3})|(?:\w{3}\s\d{1,2}))(?:\s+(?P<year>\d{4}))?)?"#
             )
             .unwrap();
         }
@@ -62,45 +201,40 @@ impl DateContext {
             return previous.clone();
         }
 
-        // Evaluate the regex and find any captures
-        let captures = RE.captures(entry.date.as_ref().unwrap()).unwrap();
+        let date_str = entry.date.as_ref().unwrap().trim();
 
-        // Helper function to retrieve matches by name, as an Option<String>
-        let cap_str = |name| {
-            captures
-                .name(name)
-                .map(|m| m.as_str().trim())
-                .filter(|s| *s != "")
-                .map(|s| s.to_lowercase())
-        };
-
-        let date = cap_str("day")
-            .map(|s| Self::parse_date_string(&s, &previous.date))
+        // Evaluate the regex and find the day and year, if any; everything after is context.
+        let captures = RE.captures(date_str);
+        let day = captures.as_ref().and_then(|captures| captures.name("day"));
+        let year = captures
+            .as_ref()
+            .and_then(|captures| captures.name("year"))
+            .map(|m| m.as_str().parse().expect("Failed to parse year!"));
+        let end = captures
+            .as_ref()
+            .and_then(|captures| captures.get(0))
+            .map(|m| m.end())
+            .unwrap_or(0);
+        let tags = parse_context_tags(&date_str[end..]);
+
+        let date = day
+            .map(|m| m.as_str().to_lowercase())
+            .map(|s| Self::parse_date_string(&s, year, &previous.date))
             .unwrap_or(previous.date.clone());
-        let context1 = cap_str("context1");
-        let context2 = cap_str("context2");
 
-        let is_time_string = |context: Option<&String>| {
-            context
-                .map(|c| TimePeriod::is_time_string(&c.as_ref()))
-                .unwrap_or(false)
-        };
+        let time_strings: Vec<&String> = tags
+            .iter()
+            .filter(|tag| TimePeriod::is_time_string(tag))
+            .collect();
 
         // I frequently just write "brunch"; if so we'll mark this as "afternoon".
-        let is_brunch =
-            Self::is_brunch_context(&context1) || Self::is_brunch_context(&context2);
-
-        let time: TimePeriod = match (
-            is_time_string(context1.as_ref()),
-            is_time_string(context2.as_ref()),
-        ) {
-            // If one of either is a time specifier, then use that value.
-            (true, false) => TimePeriod::from_str(context1.as_ref().unwrap())
-                .expect("Failed to parse time period!"),
-            (false, true) => TimePeriod::from_str(context2.as_ref().unwrap())
-                .expect("Failed to parse time period!"),
-            // If neither specify the time perioud, first check if "brunch" was present.
-            (false, false) => match is_brunch {
+        let is_brunch = tags.iter().any(|tag| tag == "brunch");
+
+        let time: TimePeriod = match time_strings.as_slice() {
+            // If one of the tags is a time specifier, then use that value.
+            [time] => TimePeriod::from_str(time).expect("Failed to parse time period!"),
+            // If none specify the time perioud, first check if "brunch" was present.
+            [] => match is_brunch {
                 // If it was, then use "afternoon"
                 true => TimePeriod::Afternoon,
                 // Otherwise, if this record is the same day as the previous,
@@ -112,18 +246,14 @@ impl DateContext {
                 },
             },
             // There should be no case of "afternoon, night" etc.
-            (true, true) => panic!(
-                "Found two time strings, {} and {}!",
-                context1.unwrap(),
-                context2.unwrap()
-            ),
+            _ => panic!("Found multiple time strings, {:?}!", time_strings),
         };
 
-        let context = vec![context1, context2]
+        let context = tags
             .iter()
             // Remove any context strings that denote the time period.
-            .filter(|c| c.is_some() && !TimePeriod::is_time_string(c.as_ref().unwrap()))
-            .map(|c| c.as_ref().unwrap().to_string())
+            .filter(|tag| !TimePeriod::is_time_string(tag))
+            .cloned()
             .collect();
 
         DateContext {
@@ -133,9 +263,36 @@ impl DateContext {
         }
     }
 
+    /// The Monday of this date's ISO week.
+    pub fn week_start(&self) -> NaiveDate {
+        let week = self.date.iso_week();
+        NaiveDate::from_isoywd(week.year(), week.week(), Weekday::Mon)
+    }
+
+    /// The Sunday of this date's ISO week.
+    pub fn week_end(&self) -> NaiveDate {
+        let week = self.date.iso_week();
+        NaiveDate::from_isoywd(week.year(), week.week(), Weekday::Sun)
+    }
+
+    /// The first day of this date's month.
+    pub fn month_start(&self) -> NaiveDate {
+        self.date.with_day(1).unwrap()
+    }
+
+    /// The last day of this date's month.
+    pub fn month_end(&self) -> NaiveDate {
+        let next_month = match self.date.month() {
+            12 => NaiveDate::from_ymd(self.date.year() + 1, 1, 1),
+            month => NaiveDate::from_ymd(self.date.year(), month + 1, 1),
+        };
+
+        next_month.pred()
+    }
+
     /// Parse a date string in the format "1 oct" or "feb 21".
-    /// Use the `previous` date as context for inferring the proper year.
-    fn parse_date_string(date: &String, previous: &NaiveDate) -> NaiveDate {
+    /// Unless the `year` was given, use the `previous` date as context for inferring it.
+    fn parse_date_string(date: &String, year: Option<i32>, previous: &NaiveDate) -> NaiveDate {
         use chrono::format::{parse, Parsed, StrftimeItems};
 
         // Where parsed date info will be saved
@@ -153,25 +310,17 @@ impl DateContext {
 
         let day = parsed.day.expect("Failed to parse day!");
         let month = parsed.month.expect("Failed to parse month");
-        let year = match day == 1 && month == 1 {
+
+        // Entries are in chronological order, so a month before that of the previous date
+        // means the year has changed, ex: "5 jan" following December 30th. An earlier day
+        // in the same month keeps the year, to allow for entries written slightly out of order.
+        let year = year.unwrap_or_else(|| match month < previous.month() {
             true => previous.year() + 1,
             false => previous.year(),
-        };
+        });
 
         NaiveDate::from_ymd(year, month, day)
     }
-
-    /// Test if the given time `context` is an `Option` containing "brunch".
-    /// 
-    /// Hacky workaround until `Option.contains()` is stabilized.
-    fn is_brunch_context(context: &Option<String>) -> bool {
-        match context { 
-            Some (c) => {
-                c.as_str() == "brunch"
-            },
-            None => false
-        }
-    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -181,14 +330,17 @@ pub struct QuantityRange {
 }
 
 impl QuantityRange {
-    pub fn from_entry(entry: &RawEntry) -> QuantityRange {
-        Self::from_str(&entry.quantity.as_ref().expect("No quantity found!")).unwrap()
+    pub fn from_entry(entry: &RawEntry) -> Result<QuantityRange> {
+        match &entry.quantity {
+            Some(quantity) => Self::from_str(quantity),
+            None => Err(Error::EntryInputError("Missing required quantity!".into())),
+        }
     }
 
     pub fn from_str<S: AsRef<str>>(quantity: S) -> Result<QuantityRange> {
         lazy_static! {
             static ref RE: Regex =
-                Regex::new(r#"(~?\d+(?:\.\d+)?)(?:\s*\-\s*(~?\d+(?:\.\d+)?))?"#).unwrap();
+                Regex::new(r#"(~?-?\d+(?:\.\d+)?)(?:\s*\-\s*(~?-?\d+(?:\.\d+)?))?"#).unwrap();
         }
 
         let captures = match RE.captures(quantity.as_ref()) {
@@ -213,31 +365,62 @@ impl QuantityRange {
         };
         let max = cap_index(2).map(Self::parse_value).unwrap_or(min);
 
-        Ok(QuantityRange {
+        let range = QuantityRange {
             min: ApproxF32::new(min.1, min.0),
             max: ApproxF32::new(max.1, max.0),
-        })
+        };
+
+        range.validate().map_err(Error::ValidationError)?;
+        Ok(range)
     }
 
-    /// Parse a strings like "2", "1.5", "~3", etc, and return a tuple
-    /// indicating whether the value is approximate, and what the base numeric value is.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// assert_eq!((false, 1f32), QuantityRange::parse_value("1"));
-    /// ```
-    fn parse_value(value: &str) -> (bool, f32) {
-        use std::str::FromStr;
+    /// Check that both bounds are positive, and the lower bound does not exceed the upper.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.min.num <= 0.0 || self.max.num <= 0.0 {
+            return Err("Quantity must be greater than zero".into());
+        }
+
+        if self.min.num > self.max.num {
+            return Err("min quantity exceeds max".into());
+        }
 
-        let is_approximate = value.starts_with("~");
-        let value = f32::from_str(value.trim_start_matches("~"))
-            .expect(&format!("Failed to parse number, '{}'!", value));
+        Ok(())
+    }
+
+    /// Get the `(min, max)` bounds of this range.
+    pub fn to_approx_f32_pair(&self) -> (ApproxF32, ApproxF32) {
+        (self.min, self.max)
+    }
 
-        (is_approximate, value)
+    /// The numeric value of the lower bound of this range.
+    pub fn min_num(&self) -> f32 {
+        self.min.num
     }
 
+    /// The numeric value of the upper bound of this range.
+    pub fn max_num(&self) -> f32 {
+        self.max.num
+    }
+
+    /// Sum the lower and upper bounds of every range separately.
+    /// The bounds of the total are approximate if that of any range is.
+    pub fn sum(ranges: &[QuantityRange]) -> QuantityRange {
+        ranges.iter().sum()
+    }
+
+    /// Parse a single numeric value; see `parse_approx_value`.
+    fn parse_value(value: &str) -> (bool, f32) {
+        parse_approx_value(value)
+    }
+
+    #[deprecated(note = "use the `Display` implementation instead")]
     pub fn print(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for QuantityRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut display = String::new();
 
         if self.min.is_approximate {
@@ -256,30 +439,73 @@ impl QuantityRange {
             display.push_str(&format!("{:.2}", self.max.num));
         }
 
-        display
+        f.pad(&display)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a QuantityRange> for QuantityRange {
+    fn sum<I: Iterator<Item = &'a QuantityRange>>(iter: I) -> QuantityRange {
+        iter.fold(
+            QuantityRange {
+                min: ApproxF32::new(0.0, false),
+                max: ApproxF32::new(0.0, false),
+            },
+            |total, range| QuantityRange {
+                min: ApproxF32::new(
+                    total.min.num + range.min.num,
+                    total.min.is_approximate || range.min.is_approximate,
+                ),
+                max: ApproxF32::new(
+                    total.max.num + range.max.num,
+                    total.max.is_approximate || range.max.is_approximate,
+                ),
+            },
+        )
+    }
+}
+
+impl std::iter::Sum<QuantityRange> for QuantityRange {
+    fn sum<I: Iterator<Item = QuantityRange>>(iter: I) -> QuantityRange {
+        let ranges: Vec<QuantityRange> = iter.collect();
+        QuantityRange::sum(&ranges)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Abv {
     pub min: ApproxF32,
     pub max: ApproxF32,
 }
 
 impl Abv {
-    pub fn from_entry(entry: &RawEntry) -> Option<Abv> {
-        if entry.abv.is_none() {
-            return None;
+    /// Build an `Abv` from separately stored min and max values, as found in the database.
+    ///
+    /// Returns `None` unless both values are present.
+    pub fn from_approx_f32_pair(min: Option<ApproxF32>, max: Option<ApproxF32>) -> Option<Abv> {
+        match (min, max) {
+            (Some(min), Some(max)) => Some(Abv { min, max }),
+            _ => None,
         }
+    }
 
-        Self::from_str(&entry.abv.as_ref().expect("No ABV found!"))
-            .expect("A minimum ABV is required!")
+    /// Check if any ABV within this range is also within the `other` range.
+    pub fn overlaps(&self, other: &Abv) -> bool {
+        self.min.num <= other.max.num && other.min.num <= self.max.num
+    }
+
+    pub fn from_entry(entry: &RawEntry) -> Result<Option<Abv>> {
+        Ok(entry
+            .abv
+            .as_ref()
+            .map(|abv| Self::from_str(abv))
+            .transpose()?
+            .flatten())
     }
 
     pub fn from_str<S: AsRef<str>>(abv: S) -> Result<Option<Abv>> {
         lazy_static! {
             static ref RE: Regex =
-                Regex::new(r#"(~?\d+(?:\.\d+)?)%?(?:\s*\-\s*(~?\d+(?:\.\d+)?)%?)?%"#).unwrap();
+                Regex::new(r#"(~?-?\d+(?:\.\d+)?)%?(?:\s*\-\s*(~?-?\d+(?:\.\d+)?)%?)?%"#).unwrap();
         }
 
         let captures = match RE.captures(abv.as_ref()) {
@@ -303,31 +529,49 @@ impl Abv {
 
         let max = cap_index(2).map(Self::parse_value).unwrap_or(min);
 
-        Ok(Some(Abv {
+        let abv = Abv {
             min: ApproxF32::new(min.1, min.0),
             max: ApproxF32::new(max.1, max.0),
-        }))
+        };
+
+        abv.validate().map_err(Error::ValidationError)?;
+        Ok(Some(abv))
     }
 
-    /// Parse a strings like "2", "1.5", "~3", etc, and return a tuple
-    /// indicating whether the value is approximate, and what the base numeric value is.
+    /// Check that both bounds are a possible ABV, from 0% to 100%,
+    /// and that the lower bound does not exceed the upper.
     ///
-    /// # Examples
-    ///
-    /// ```
-    /// assert_eq!((false, 1f32), QuantityRange::parse_value("1"));
-    /// ```
-    fn parse_value(value: &str) -> (bool, f32) {
-        use std::str::FromStr;
+    /// Unlike quantities, an ABV of zero is allowed, for alcohol-free drinks.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for bound in &[&self.min, &self.max] {
+            if bound.num < 0.0 || bound.num > 100.0 {
+                return Err(format!(
+                    "ABV must be between 0% and 100%, found {}%",
+                    bound.num
+                ));
+            }
+        }
+
+        if self.min.num > self.max.num {
+            return Err("min ABV exceeds max".into());
+        }
 
-        let is_approximate = value.starts_with("~");
-        let value = f32::from_str(value.trim_start_matches("~"))
-            .expect(&format!("Failed to parse number, '{}'!", value));
+        Ok(())
+    }
 
-        (is_approximate, value)
+    /// Parse a single numeric value; see `parse_approx_value`.
+    fn parse_value(value: &str) -> (bool, f32) {
+        parse_approx_value(value)
     }
 
+    #[deprecated(note = "use the `Display` implementation instead")]
     pub fn print(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for Abv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut display = String::new();
 
         if self.min.is_approximate {
@@ -348,7 +592,7 @@ impl Abv {
 
         display.push('%');
 
-        display
+        f.pad(&display)
     }
 }
 
@@ -359,11 +603,16 @@ pub struct VolumeContext {
 
 impl VolumeContext {
     pub fn from_entry(entry: &RawEntry) -> Option<VolumeContext> {
+        Self::from_entry_with_context(entry, &[])
+    }
+
+    /// Same as `from_entry`, but uses the date `context` to resolve ambiguous units.
+    pub fn from_entry_with_context(entry: &RawEntry, context: &[String]) -> Option<VolumeContext> {
         if entry.volume.is_none() {
             return None;
         }
 
-        match Self::from_str(entry.volume.as_ref().unwrap()) {
+        match Self::from_str_with_context(entry.volume.as_ref().unwrap(), context) {
             Ok(volume) => volume,
             Err(e) => {
                 match e {
@@ -378,12 +627,42 @@ impl VolumeContext {
     }
 
     pub fn from_str<S: AsRef<str>>(volume: S) -> Result<Option<VolumeContext>> {
+        Self::parse(volume.as_ref(), VolumeUnit::UsPint)
+    }
+
+    /// Parse a volume string, using the entry's date `context` to decide
+    /// whether a bare "pint" means a UK pint or a US pint.
+    pub fn from_str_with_context(
+        volume: &str,
+        context: &[String],
+    ) -> Result<Option<VolumeContext>> {
+        let pint = match Self::is_british_context(context) {
+            true => VolumeUnit::UkPint,
+            false => VolumeUnit::UsPint,
+        };
+
+        Self::parse(volume, pint)
+    }
+
+    /// Test if any of the `context` strings suggest the drink was had in the UK or Ireland.
+    fn is_british_context(context: &[String]) -> bool {
+        const BRITISH_CONTEXTS: [&str; 5] = ["uk", "england", "ireland", "pub", "british"];
+
+        context
+            .iter()
+            .any(|c| BRITISH_CONTEXTS.contains(&c.trim().to_lowercase().as_str()))
+    }
+
+    /// Parse a volume string, treating an unqualified "pint" as the `pint` unit.
+    fn parse(volume: &str, pint: VolumeUnit) -> Result<Option<VolumeContext>> {
         lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r#"(?P<volume>~?\d+(?:\.\d+)?)\s*(?P<unit>\w{2,})"#).unwrap();
+            static ref RE: Regex = Regex::new(
+                r#"(?i)(?P<volume>~?\d+(?:\.\d+)?)\s*(?P<unit>fl\s+oz|(?:us|uk|imperial)\s+pint|[a-z]+)"#
+            )
+            .unwrap();
         }
 
-        let captures = match RE.captures(volume.as_ref()) {
+        let captures = match RE.captures(volume) {
             Some(c) => c,
             None => {
                 return Ok(None);
@@ -396,6 +675,8 @@ impl VolumeContext {
                 .name(name)
                 .map(|m| m.as_str().trim())
                 .filter(|s| *s != "")
+                // Collapse any repeated whitespace, as in "fl   oz".
+                .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
                 .map(|s| s.to_lowercase())
         };
 
@@ -409,6 +690,14 @@ impl VolumeContext {
         let (is_approximate, volume_amount) = Self::parse_value(volume_str.as_ref().unwrap());
 
         let unit = match VolumeUnit::from_str(unit_str.as_ref().unwrap().as_ref()) {
+            // Only a bare "pint" or "pt" is ambiguous; "us pint" or "uk pint" are left as given.
+            Some(unit) if unit.is_pint() && !unit_str.as_ref().unwrap().contains(' ') => {
+                if pint == VolumeUnit::UsPint {
+                    log::warn!("Assuming '{}' is in US pints, without a UK context", volume);
+                }
+
+                pint
+            }
             Some(unit) => unit,
             None => {
                 return Err(Error::EntryInputError(format!(
@@ -427,17 +716,45 @@ impl VolumeContext {
         }))
     }
 
+    /// Parse a single numeric value; see `parse_approx_value`.
     pub fn parse_value(value: &str) -> (bool, f32) {
-        use std::str::FromStr;
+        parse_approx_value(value)
+    }
+
+    #[deprecated(note = "use the `Display` implementation instead")]
+    pub fn print(&self) -> String {
+        self.to_string()
+    }
+
+    /// Format this volume for a CSV export, such that importing it again gives the same volume.
+    ///
+    /// The unit is written as `original_unit` when known, unless that was an ambiguous pint
+    /// which the entry's context resolved to a different pint. Fluid ounces are written
+    /// as "oz", the way they are usually recorded.
+    pub fn to_csv_string(&self) -> String {
+        let unit = match self.original_unit {
+            Some(unit) if !unit.is_pint() || unit == self.volume.unit => unit,
+            _ => self.volume.unit,
+        };
+        let unit = match unit {
+            VolumeUnit::FlOz => "oz",
+            unit => unit.to_str(),
+        };
+
+        let mut display = String::new();
 
-        let is_approximate = value.starts_with("~");
-        let value = f32::from_str(value.trim_start_matches("~"))
-            .expect(&format!("Failed to parse number, '{}'!", value));
+        if self.volume.amount.is_approximate {
+            display.push('~');
+        }
+
+        display.push_str(&format!("{:.2} {}", self.volume.amount.num, unit));
 
-        (is_approximate, value)
+        display
     }
+}
 
-    pub fn print(&self) -> String {
+impl std::fmt::Display for VolumeContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut display = String::new();
 
         if self.volume.amount.is_approximate {
@@ -448,11 +765,11 @@ impl VolumeContext {
         display.push_str(" ");
         display.push_str(self.volume.unit.to_str());
 
-        display
+        f.pad(&display)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Drink {
     pub name: String,
     pub abv: Option<Abv>,
@@ -460,35 +777,67 @@ pub struct Drink {
 }
 
 impl Drink {
-    pub fn from_entry(entry: &RawEntry) -> Drink {
+    pub fn from_entry(entry: &RawEntry) -> Result<Drink> {
+        Self::from_entry_with_config(entry, &ImportConfig::default())
+    }
+
+    /// Parse a drink, using the keywords in `config` to detect doubles, triples, etc.
+    pub fn from_entry_with_config(entry: &RawEntry, config: &ImportConfig) -> Result<Drink> {
         let multiplier = entry
             .name
             .as_ref()
-            .map(|name| match name.contains("double") {
-                true => 2.0,
-                false => 1.0,
-            })
+            .map(|name| config.multiplier(name))
             .unwrap_or(1.0);
 
-        Drink {
+        Ok(Drink {
             name: entry
                 .name
                 .as_ref()
                 .expect("Missing drink name!")
                 .trim()
                 .to_lowercase(),
-            abv: Abv::from_entry(entry),
+            abv: Abv::from_entry(entry)?,
             multiplier: multiplier,
+        })
+    }
+
+    /// Build a drink from the drink details included with an entry from the database.
+    pub fn from_db_entry(entry: &db::Entry) -> Drink {
+        Drink {
+            name: entry.name.clone(),
+            abv: Abv::from_approx_f32_pair(entry.min_abv, entry.max_abv),
+            multiplier: entry.multiplier,
         }
     }
 }
 
+impl Drink {
+    /// The multiplier as an integer, for the purposes of comparison and hashing.
+    fn multiplier_key(&self) -> i32 {
+        (self.multiplier * 100.0).trunc() as i32
+    }
+
+    /// Check if `other` is the same beverage, regardless of how it was poured;
+    /// ex: a single and a double of the same whiskey.
+    pub fn same_beverage(&self, other: &Drink) -> bool {
+        self.name == other.name && self.abv == other.abv
+    }
+
+    /// A stable, human readable key identifying this beverage, ex: "pale ale|5.5%".
+    ///
+    /// Like `same_beverage`, the key does not include the multiplier.
+    pub fn canonical_key(&self) -> String {
+        let abv = self.abv.as_ref().map(|a| a.to_string());
+
+        format!("{}|{}", self.name, abv.unwrap_or("none".into()))
+    }
+}
+
 impl PartialEq for Drink {
     fn eq(&self, other: &Drink) -> bool {
         self.name == other.name
             && self.abv == other.abv
-            && ((self.multiplier * 100.0).trunc() as i32)
-                == ((other.multiplier * 100.0).trunc() as i32)
+            && self.multiplier_key() == other.multiplier_key()
     }
 }
 
@@ -498,15 +847,39 @@ impl Hash for Drink {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.name.hash(state);
         self.abv.hash(state);
-        ((self.multiplier * 100.0).trunc() as i32).hash(state);
+        self.multiplier_key().hash(state);
     }
 }
 
+/// A set of drinks, keyed by their database IDs.
+///
+/// Serializes as `{ "drinks": { "<id>": <drink>, ... } }`, so that it can be cached
+/// between imports; the reverse lookup is rebuilt when it's deserialized.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "SerializedDrinkSet")]
 pub struct DrinkSet {
     drinks: HashMap<i32, Drink>,
+
+    #[serde(skip)]
     lookup: HashMap<Drink, i32>,
 }
 
+#[derive(Deserialize)]
+struct SerializedDrinkSet {
+    drinks: HashMap<i32, Drink>,
+}
+
+impl From<SerializedDrinkSet> for DrinkSet {
+    fn from(serialized: SerializedDrinkSet) -> DrinkSet {
+        let mut drink_set = DrinkSet::new();
+        for (id, drink) in serialized.drinks {
+            drink_set.insert(id, drink);
+        }
+
+        drink_set
+    }
+}
+
 impl DrinkSet {
     pub fn new() -> DrinkSet {
         DrinkSet {
@@ -519,6 +892,56 @@ impl DrinkSet {
         self.lookup.get(drink).map(|id| *id)
     }
 
+    /// Get the drink with the given `id`.
+    pub fn get(&self, id: i32) -> Option<&Drink> {
+        self.drinks.get(&id)
+    }
+
+    /// Find any drink which is the same beverage, regardless of its multiplier.
+    pub fn find_beverage(&self, drink: &Drink) -> Option<i32> {
+        self.lookup
+            .iter()
+            .filter(|(other, _)| other.same_beverage(drink))
+            .map(|(_, id)| *id)
+            // Prefer the earliest record, so repeated lookups are consistent.
+            .min()
+    }
+
+    /// Find the drink with the given `canonical_key`.
+    /// If several drinks share the key, the earliest record is returned.
+    pub fn find_by_key(&self, key: &str) -> Option<(i32, &Drink)> {
+        self.drinks
+            .iter()
+            .filter(|(_, drink)| drink.canonical_key() == key)
+            .min_by_key(|(id, _)| **id)
+            .map(|(id, drink)| (*id, drink))
+    }
+
+    /// Find a drink, treating ABVs which differ by less than `abv_epsilon` as the same.
+    pub fn find_fuzzy(&self, drink: &Drink, abv_epsilon: f32) -> Option<i32> {
+        if let Some(id) = self.find(drink) {
+            return Some(id);
+        }
+
+        let abv_matches = |a: &Option<Abv>, b: &Option<Abv>| match (a, b) {
+            (Some(a), Some(b)) => {
+                a.min.approximately_equals(&b.min, abv_epsilon)
+                    && a.max.approximately_equals(&b.max, abv_epsilon)
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        self.lookup
+            .iter()
+            .find(|(other, _)| {
+                other.name == drink.name
+                    && other.multiplier_key() == drink.multiplier_key()
+                    && abv_matches(&other.abv, &drink.abv)
+            })
+            .map(|(_, id)| *id)
+    }
+
     pub fn insert(&mut self, id: i32, drink: Drink) -> i32 {
         assert!(self.drinks
             .insert(id, drink.clone())
@@ -529,11 +952,53 @@ impl DrinkSet {
 
         id
     }
+
+    /// Remove `drink` from the set, returning whether it was present.
+    pub fn remove(&mut self, drink: &Drink) -> bool {
+        match self.lookup.remove(drink) {
+            Some(id) => {
+                let removed = self.drinks.remove(&id);
+                assert!(removed.is_some(), "Drink lookup was out of sync!");
+                self.assert_consistent();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the drink with the given `id`, returning whether it was present.
+    pub fn remove_by_id(&mut self, id: i32) -> bool {
+        match self.drinks.remove(&id) {
+            Some(drink) => {
+                let removed = self.lookup.remove(&drink);
+                assert_eq!(Some(id), removed, "Drink lookup was out of sync!");
+                self.assert_consistent();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn assert_consistent(&self) {
+        assert_eq!(
+            self.drinks.len(),
+            self.lookup.len(),
+            "Drink lookup was out of sync!"
+        );
+    }
 }
 
 #[cfg(test)]
+#[deny(dead_code)]
 mod tests {
-    use super::{Abv, QuantityRange, RawEntry};
+    use super::{
+        detect_year_rollover, parse_context_tags, Abv, DateContext, Drink, DrinkSet, QuantityRange,
+        RawEntry, VolumeContext,
+    };
+    use crate::db;
+    use crate::error::Error;
+    use crate::models::{ApproxF32, LiquidVolume, TimePeriod, VolumeUnit};
+    use chrono::{NaiveDate, Utc};
 
     #[test]
     fn test_quantity_range_parse_value() {
@@ -547,7 +1012,7 @@ mod tests {
         let test = |range_tuple, entry_str| {
             assert_eq!(
                 make_range(range_tuple),
-                QuantityRange::from_entry(&make_quantity_entry(entry_str))
+                QuantityRange::from_entry(&make_quantity_entry(entry_str)).unwrap()
             );
         };
         test((false, 1.0, false, 1.0), "1");
@@ -569,12 +1034,62 @@ mod tests {
         test((false, 1.0, false, 2.0), "1-2");
     }
 
+    #[test]
+    fn test_quantity_range_validate() {
+        assert!(make_range((false, 1.0, false, 2.0)).validate().is_ok());
+        assert!(make_range((false, 2.0, false, 2.0)).validate().is_ok());
+        assert_eq!(
+            Err("min quantity exceeds max".into()),
+            make_range((false, 5.0, false, 2.0)).validate()
+        );
+        assert!(make_range((false, 0.0, false, 2.0)).validate().is_err());
+        assert!(make_range((false, 0.0, false, 0.0)).validate().is_err());
+
+        assert!(QuantityRange::from_str("5-2").is_err());
+        assert!(QuantityRange::from_str("0").is_err());
+        assert!(QuantityRange::from_entry(&make_quantity_entry("5 - 2")).is_err());
+    }
+
+    #[test]
+    fn test_quantity_range_non_positive() {
+        let validation_error = |quantity: &str| match QuantityRange::from_str(quantity) {
+            Err(Error::ValidationError(message)) => Some(message),
+            _ => None,
+        };
+
+        let not_positive = Some("Quantity must be greater than zero".to_string());
+        assert_eq!(not_positive, validation_error("0"));
+        assert_eq!(not_positive, validation_error("-1"));
+        assert_eq!(not_positive, validation_error("~-1"));
+        assert_eq!(not_positive, validation_error("0 - 2"));
+
+        assert_eq!(
+            Some("min quantity exceeds max".to_string()),
+            validation_error("3 - 2")
+        );
+        assert!(QuantityRange::from_str("2 - 3").is_ok());
+    }
+
+    #[test]
+    fn test_abv_validate() {
+        assert!(make_abv((false, 4.5, false, 5.0)).validate().is_ok());
+        assert!(make_abv((false, 0.0, false, 0.0)).validate().is_ok());
+        assert!(make_abv((false, 6.0, false, 5.0)).validate().is_err());
+        assert!(make_abv((false, -1.0, false, 5.0)).validate().is_err());
+        assert!(make_abv((false, 5.0, false, 100.5)).validate().is_err());
+
+        assert!(Abv::from_str("6-5%").is_err());
+        assert!(Abv::from_str("0%").unwrap().is_some());
+    }
+
     #[test]
     fn test_abv_parse() {
         let test = |abv_tuple, entry_str| {
             assert_eq!(
                 make_abv(abv_tuple),
-                Abv::from_entry(&make_abv_entry(entry_str)).unwrap()
+                Abv::from_entry(&make_abv_entry(entry_str))
+                    .unwrap()
+                    .unwrap()
             );
         };
         test((false, 1.0, false, 1.0), "1%");
@@ -609,6 +1124,662 @@ mod tests {
         test((false, 1.0, false, 2.0), "1%-2%");
     }
 
+    #[test]
+    fn test_display() {
+        let approx = |num, is_approximate| ApproxF32::new(num, is_approximate);
+
+        let abv = |min, max| Abv { min, max };
+        assert_eq!(
+            "5.0%",
+            abv(approx(5.0, false), approx(5.0, false)).to_string()
+        );
+        assert_eq!(
+            "4.0-5.0%",
+            abv(approx(4.0, false), approx(5.0, false)).to_string()
+        );
+        assert_eq!(
+            "~4.0-~5.5%",
+            abv(approx(4.0, true), approx(5.5, true)).to_string()
+        );
+
+        let quantity = |min, max| QuantityRange { min, max };
+        assert_eq!(
+            "2.00",
+            quantity(approx(2.0, false), approx(2.0, false)).to_string()
+        );
+        assert_eq!(
+            "1.00-~2.50",
+            quantity(approx(1.0, false), approx(2.5, true)).to_string()
+        );
+
+        let volume = VolumeContext {
+            volume: LiquidVolume {
+                amount: approx(12.0, true),
+                unit: VolumeUnit::FlOz,
+            },
+            original_unit: None,
+        };
+        assert_eq!("~12.00 fl oz", volume.to_string());
+
+        // Width and alignment are respected, for tabular output.
+        let single = abv(approx(5.0, false), approx(5.0, false));
+        assert_eq!("5.0%  |", format!("{:6}|", single));
+        assert_eq!("  5.0%|", format!("{:>6}|", single));
+    }
+
+    #[test]
+    fn test_volume_context_to_csv_string() {
+        let round_trip = |input: &str, context: &[String]| {
+            let parsed = VolumeContext::from_str_with_context(input, context)
+                .unwrap()
+                .unwrap();
+            let csv = parsed.to_csv_string();
+            let reparsed = VolumeContext::from_str_with_context(&csv, context)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(parsed.volume.amount, reparsed.volume.amount, "{}", input);
+            assert_eq!(parsed.volume.unit, reparsed.volume.unit, "{}", input);
+            csv
+        };
+
+        assert_eq!("12.00 oz", round_trip("12 oz", &[]));
+        assert_eq!("12.00 oz", round_trip("12 fl oz", &[]));
+        assert_eq!("~330.00 mL", round_trip("~330 mL", &[]));
+
+        // A pint resolved from the context is written unambiguously.
+        let pub_context = vec!["pub".to_string()];
+        assert_eq!("1.00 uk pint", round_trip("1 pint", &pub_context));
+        assert_eq!("1.00 us pint", round_trip("1 pint", &[]));
+        assert_eq!("2.00 uk pint", round_trip("2 pt", &pub_context));
+        assert_eq!("1.00 uk pint", round_trip("1 imperial pint", &[]));
+
+        let no_original_unit = VolumeContext {
+            volume: LiquidVolume {
+                amount: ApproxF32::new(50.0, false),
+                unit: VolumeUnit::cL,
+            },
+            original_unit: None,
+        };
+        assert_eq!("50.00 cL", no_original_unit.to_csv_string());
+    }
+
+    #[test]
+    fn test_volume_context_from_str() {
+        let test = |input: &str, amount: f32, unit: VolumeUnit, is_approximate: bool| {
+            let context = VolumeContext::from_str(input)
+                .unwrap()
+                .expect(&format!("Failed to parse '{}'!", input));
+
+            let volume = context.volume;
+            assert_eq!(amount, volume.amount.num, "{}", input);
+            assert_eq!(unit, volume.unit, "{}", input);
+            assert_eq!(is_approximate, volume.amount.is_approximate, "{}", input);
+            assert_eq!(Some(unit), context.original_unit, "{}", input);
+        };
+
+        test("330 mL", 330.0, VolumeUnit::mL, false);
+        test("33 cL", 33.0, VolumeUnit::cL, false);
+        test("0.33 L", 0.33, VolumeUnit::L, false);
+        test("12 fl oz", 12.0, VolumeUnit::FlOz, false);
+        test("12 oz", 12.0, VolumeUnit::FlOz, false);
+        test("~330 mL", 330.0, VolumeUnit::mL, true);
+        test("~12 oz", 12.0, VolumeUnit::FlOz, true);
+        test("1 uk pint", 1.0, VolumeUnit::UkPint, false);
+        test("2 US Pints", 2.0, VolumeUnit::UsPint, false);
+        test("1 Imperial Pint", 1.0, VolumeUnit::UkPint, false);
+        test("1 pint", 1.0, VolumeUnit::UsPint, false);
+        test("2 pt", 2.0, VolumeUnit::UsPint, false);
+        test("5 gallons", 5.0, VolumeUnit::Gallon, false);
+        test("~1 gal", 1.0, VolumeUnit::Gallon, true);
+        test("2 quarts", 2.0, VolumeUnit::Quart, false);
+        test("1 qt", 1.0, VolumeUnit::Quart, false);
+    }
+
+    #[test]
+    fn test_volume_context_from_str_invalid() {
+        assert!(VolumeContext::from_str("5 furlongs").is_err());
+        assert!(VolumeContext::from_str("").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_volume_pint_context() {
+        let parse = |context: &[&str]| {
+            let context: Vec<String> = context.iter().map(|c| c.to_string()).collect();
+            VolumeContext::from_str_with_context("1 pint", &context)
+                .unwrap()
+                .expect("Failed to parse pint volume!")
+        };
+
+        assert_eq!(VolumeUnit::UkPint, parse(&["pub"]).volume.unit);
+        assert_eq!(VolumeUnit::UkPint, parse(&["Ireland"]).volume.unit);
+        assert_eq!(VolumeUnit::UsPint, parse(&["bar"]).volume.unit);
+        assert_eq!(VolumeUnit::UsPint, parse(&[]).volume.unit);
+        assert_eq!(1.0, parse(&["pub"]).volume.amount.num);
+
+        let pt = VolumeContext::from_str_with_context("1 pt", &["pub".to_string()]).unwrap();
+        assert_eq!(VolumeUnit::UkPint, pt.unwrap().volume.unit);
+
+        // An explicit pint is never changed by the context.
+        let explicit = |volume: &str, context: &[&str]| {
+            let context: Vec<String> = context.iter().map(|c| c.to_string()).collect();
+            VolumeContext::from_str_with_context(volume, &context)
+                .unwrap()
+                .expect("Failed to parse pint volume!")
+                .volume
+                .unit
+        };
+
+        assert_eq!(VolumeUnit::UsPint, explicit("1 us pint", &["pub"]));
+        assert_eq!(VolumeUnit::UsPint, explicit("1 US  pint", &[]));
+        assert_eq!(VolumeUnit::UkPint, explicit("1 uk pint", &["bar"]));
+        assert_eq!(VolumeUnit::UkPint, explicit("1 UK pint", &[]));
+    }
+
+    #[test]
+    fn test_raw_entry_from_csv_record() {
+        let entry = RawEntry::from_csv_record(csv::StringRecord::from(vec![
+            "(1 oct", " bar) 2", "IPA", "6.5%", "12 oz",
+        ]))
+        .unwrap();
+
+        assert_eq!(Some("1 oct, bar"), entry.date.as_deref());
+        assert_eq!(Some("2"), entry.quantity.as_deref());
+        assert_eq!(Some("IPA"), entry.name.as_deref());
+        assert_eq!(Some("6.5%"), entry.abv.as_deref());
+        assert_eq!(Some("12 oz"), entry.volume.as_deref());
+
+        let entry = RawEntry::from_csv_record(csv::StringRecord::from(vec!["1", "beer"])).unwrap();
+
+        assert_eq!(None, entry.date);
+        assert_eq!(Some("1"), entry.quantity.as_deref());
+        assert_eq!(Some("beer"), entry.name.as_deref());
+        assert_eq!(None, entry.abv);
+
+        assert!(RawEntry::from_csv_record(csv::StringRecord::from(vec!["(1 oct"])).is_none());
+    }
+
+    #[test]
+    fn test_raw_entry_ignored_lines() {
+        assert!(RawEntry::is_ignored_line(""));
+        assert!(RawEntry::is_ignored_line("   "));
+        assert!(RawEntry::is_ignored_line("# New Year's Eve"));
+        assert!(RawEntry::is_ignored_line("  #2, beer"));
+        assert!(!RawEntry::is_ignored_line("2, beer # with friends"));
+
+        assert!(RawEntry::from_line("# 2, beer").is_none());
+        assert!(RawEntry::from_line("").is_none());
+        assert!(RawEntry::from_line("2, beer").is_some());
+    }
+
+    #[test]
+    fn test_raw_entry_from_json_str() {
+        let entry = RawEntry::from_json_str(
+            r#"{"date": "1 oct; bar", "quantity": 2, "name": "IPA", "volume": "12 oz"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("1 oct; bar"), entry.date.as_deref());
+        assert_eq!(Some("2"), entry.quantity.as_deref());
+        assert_eq!(Some("IPA"), entry.name.as_deref());
+        assert_eq!(None, entry.abv);
+        assert_eq!(Some("12 oz"), entry.volume.as_deref());
+
+        // Not valid JSON
+        assert!(RawEntry::from_json_str(r#"{"quantity": "2", "name": "#).is_none());
+        // Not an object
+        assert!(RawEntry::from_json_str(r#"["2", "beer"]"#).is_none());
+        // Missing the drink name
+        assert!(RawEntry::from_json_str(r#"{"quantity": "2"}"#).is_none());
+    }
+
+    #[test]
+    fn test_drink_set_find_fuzzy() {
+        let drink = |abv: f32| Drink {
+            name: "pale ale".into(),
+            abv: Some(make_abv((false, abv, false, abv))),
+            multiplier: 1.0,
+        };
+
+        let mut drinks = DrinkSet::new();
+        drinks.insert(1, drink(5.0));
+
+        assert_eq!(Some(1), drinks.find_fuzzy(&drink(5.0), 0.01));
+        assert_eq!(Some(1), drinks.find_fuzzy(&drink(5.005), 0.01));
+        assert_eq!(None, drinks.find(&drink(5.005)));
+        assert_eq!(None, drinks.find_fuzzy(&drink(5.1), 0.01));
+
+        let unknown_abv = Drink {
+            abv: None,
+            ..drink(5.0)
+        };
+        assert_eq!(None, drinks.find_fuzzy(&unknown_abv, 0.01));
+    }
+
+    #[test]
+    fn test_drink_set_find_beverage() {
+        let whiskey = |multiplier: f32| Drink {
+            name: "whiskey".into(),
+            abv: Some(make_abv((false, 40.0, false, 40.0))),
+            multiplier,
+        };
+
+        assert!(whiskey(1.0).same_beverage(&whiskey(2.0)));
+        assert_ne!(whiskey(1.0), whiskey(2.0));
+
+        let mut drinks = DrinkSet::new();
+        drinks.insert(3, whiskey(1.0));
+
+        assert_eq!(None, drinks.find(&whiskey(2.0)));
+        assert_eq!(Some(3), drinks.find_beverage(&whiskey(2.0)));
+
+        let rum = Drink {
+            name: "rum".into(),
+            ..whiskey(2.0)
+        };
+        assert_eq!(None, drinks.find_beverage(&rum));
+    }
+
+    #[test]
+    fn test_drink_canonical_key() {
+        let ipa = Drink {
+            name: "ipa".into(),
+            abv: Some(make_abv((false, 6.5, false, 6.5))),
+            multiplier: 1.0,
+        };
+        let unknown = Drink {
+            name: "mystery beer".into(),
+            abv: None,
+            multiplier: 2.0,
+        };
+
+        assert_eq!("ipa|6.5%", ipa.canonical_key());
+        assert_eq!("mystery beer|none", unknown.canonical_key());
+
+        let double_ipa = Drink {
+            multiplier: 2.0,
+            ..ipa.clone()
+        };
+        assert_eq!(ipa.canonical_key(), double_ipa.canonical_key());
+
+        let mut drinks = DrinkSet::new();
+        drinks.insert(4, double_ipa);
+        drinks.insert(2, ipa.clone());
+        drinks.insert(3, unknown.clone());
+
+        assert_eq!(Some((2, &ipa)), drinks.find_by_key("ipa|6.5%"));
+        assert_eq!(Some((3, &unknown)), drinks.find_by_key("mystery beer|none"));
+        assert_eq!(None, drinks.find_by_key("ipa|7.0%"));
+    }
+
+    #[test]
+    fn test_drink_set_remove() {
+        let drink = |name: &str| Drink {
+            name: name.into(),
+            abv: Some(make_abv((false, 5.0, false, 5.0))),
+            multiplier: 1.0,
+        };
+
+        let mut drinks = DrinkSet::new();
+        drinks.insert(1, drink("lager"));
+        drinks.insert(2, drink("stout"));
+
+        assert!(drinks.remove(&drink("lager")));
+        assert_eq!(None, drinks.find(&drink("lager")));
+        assert!(drinks.get(1).is_none());
+        assert!(!drinks.remove(&drink("lager")));
+        assert!(!drinks.remove(&drink("cider")));
+
+        assert!(drinks.remove_by_id(2));
+        assert_eq!(None, drinks.find(&drink("stout")));
+        assert!(!drinks.remove_by_id(2));
+        assert!(!drinks.remove_by_id(3));
+
+        // Removed drinks can be inserted again.
+        drinks.insert(1, drink("lager"));
+        assert_eq!(Some(1), drinks.find(&drink("lager")));
+    }
+
+    #[test]
+    fn test_drink_set_serde_round_trip() {
+        let drink = |name: &str, abv: Option<Abv>, multiplier| Drink {
+            name: name.into(),
+            abv,
+            multiplier,
+        };
+
+        let mut drinks = DrinkSet::new();
+        let (beer_abv, spirit_abv) = (
+            make_abv((false, 5.0, false, 5.0)),
+            make_abv((true, 40.0, true, 43.0)),
+        );
+        drinks.insert(1, drink("lager", Some(beer_abv), 1.0));
+        drinks.insert(2, drink("whiskey", Some(spirit_abv), 2.0));
+        drinks.insert(7, drink("mystery beer", None, 1.0));
+
+        let json = serde_json::to_value(&drinks).unwrap();
+        assert_eq!("lager", json["drinks"]["1"]["name"]);
+        assert_eq!(2.0, json["drinks"]["2"]["multiplier"]);
+        assert!(json.get("lookup").is_none());
+
+        let restored: DrinkSet = serde_json::from_value(json).unwrap();
+        for &id in [1, 2, 7].iter() {
+            let original = drinks.get(id).unwrap();
+            assert_eq!(Some(original), restored.get(id));
+            assert_eq!(Some(id), restored.find(original));
+        }
+        assert!(restored.get(3).is_none());
+
+        let empty: DrinkSet = serde_json::from_str(r#"{ "drinks": {} }"#).unwrap();
+        assert!(empty.get(1).is_none());
+    }
+
+    #[test]
+    fn test_abv_from_approx_f32_pair() {
+        let min = ApproxF32::new(4.5, true);
+        let max = ApproxF32::new(5.0, false);
+
+        assert_eq!(
+            Some(Abv { min, max }),
+            Abv::from_approx_f32_pair(Some(min), Some(max))
+        );
+        assert_eq!(None, Abv::from_approx_f32_pair(Some(min), None));
+        assert_eq!(None, Abv::from_approx_f32_pair(None, Some(max)));
+        assert_eq!(None, Abv::from_approx_f32_pair(None, None));
+    }
+
+    #[test]
+    fn test_quantity_range_sum() {
+        let zero = QuantityRange::sum(&[]);
+        assert_eq!(make_range((false, 0.0, false, 0.0)), zero);
+
+        let single = QuantityRange::sum(&[make_range((true, 1.0, false, 2.0))]);
+        assert_eq!(make_range((true, 1.0, false, 2.0)), single);
+
+        let ranges = vec![
+            make_range((false, 1.0, false, 2.0)),
+            make_range((false, 0.5, true, 1.5)),
+            make_range((false, 3.0, false, 3.0)),
+        ];
+        assert_eq!(
+            make_range((false, 4.5, true, 6.5)),
+            QuantityRange::sum(&ranges)
+        );
+        assert_eq!(
+            make_range((false, 4.5, true, 6.5)),
+            ranges.into_iter().sum::<QuantityRange>()
+        );
+    }
+
+    #[test]
+    fn test_drink_from_db_entry() {
+        let entry = db::Entry {
+            id: 1,
+            drank_on: NaiveDate::from_ymd(2020, 5, 4),
+            time: TimePeriod::Evening,
+            context: Vec::new(),
+            drink_id: 1,
+            name: "whiskey".into(),
+            min_abv: Some(ApproxF32::new(40.0, false)),
+            max_abv: Some(ApproxF32::new(43.0, true)),
+            multiplier: 2.0,
+            min_quantity: ApproxF32::new(1.0, false),
+            max_quantity: ApproxF32::new(1.0, false),
+            volume: None,
+            volume_ml: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let drink = Drink::from_db_entry(&entry);
+        assert_eq!("whiskey", drink.name);
+        assert_eq!(Some(make_abv((false, 40.0, true, 43.0))), drink.abv);
+        assert_eq!(2.0, drink.multiplier);
+
+        let without_abv = db::Entry {
+            min_abv: None,
+            max_abv: None,
+            ..entry
+        };
+        assert_eq!(None, Drink::from_db_entry(&without_abv).abv);
+    }
+
+    #[test]
+    fn test_abv_out_of_range() {
+        let is_validation_error = |abv: &str| match Abv::from_str(abv) {
+            Err(Error::ValidationError(_)) => true,
+            _ => false,
+        };
+
+        assert!(is_validation_error("150%"));
+        assert!(is_validation_error("-5%"));
+        assert!(is_validation_error("10%-5%"));
+
+        assert!(Abv::from_str("100%").unwrap().is_some());
+        assert!(Abv::from_str("0-5%").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_abv_from_entry_errors() {
+        assert!(Abv::from_entry(&make_abv_entry("150%")).is_err());
+        assert!(Abv::from_entry(&make_abv_entry("5%")).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_abv_overlaps() {
+        let abv = make_abv((false, 4.5, false, 5.5));
+
+        assert!(abv.overlaps(&make_abv((false, 5.0, false, 5.0))));
+        assert!(abv.overlaps(&make_abv((false, 5.5, false, 7.0))));
+        assert!(abv.overlaps(&make_abv((false, 3.0, false, 8.0))));
+        assert!(!abv.overlaps(&make_abv((false, 5.6, false, 7.0))));
+        assert!(!abv.overlaps(&make_abv((false, 3.0, false, 4.4))));
+    }
+
+    #[test]
+    fn test_quantity_range_accessors() {
+        let quantity = QuantityRange::from_str("~2 - 3.5").unwrap();
+
+        let (min, max) = quantity.to_approx_f32_pair();
+        assert_eq!(quantity.min, min);
+        assert_eq!(quantity.max, max);
+        assert_eq!(quantity.min.num, quantity.min_num());
+        assert_eq!(quantity.max.num, quantity.max_num());
+
+        assert_eq!(2.0, quantity.min_num());
+        assert_eq!(3.5, quantity.max_num());
+        assert!(min.is_approximate);
+        assert!(!max.is_approximate);
+    }
+
+    #[test]
+    fn test_parse_context_tags() {
+        assert_eq!(vec!["pub", "birthday"], parse_context_tags("pub, birthday"));
+        assert_eq!(vec!["pub", "birthday"], parse_context_tags("Pub; Birthday"));
+        assert_eq!(
+            vec!["home", "pub", "evening"],
+            parse_context_tags(" home;pub , ,evening;")
+        );
+        assert!(parse_context_tags("").is_empty());
+        assert!(parse_context_tags(" ;, ").is_empty());
+    }
+
+    #[test]
+    fn test_date_context_from_entry() {
+        let previous = make_date_context((2020, 5, 4));
+        let entry = |date: &str| RawEntry {
+            date: Some(date.into()),
+            quantity: None,
+            name: None,
+            abv: None,
+            volume: None,
+        };
+
+        let context = DateContext::from_entry(&entry("5 may, pub; evening"), &previous);
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 5), context.date);
+        assert_eq!(TimePeriod::Evening, context.time);
+        assert_eq!(vec!["pub"], context.context);
+
+        let context = DateContext::from_entry(&entry("5 may 2019; pub; evening"), &previous);
+        assert_eq!(NaiveDate::from_ymd(2019, 5, 5), context.date);
+        assert_eq!(TimePeriod::Evening, context.time);
+        assert_eq!(vec!["pub"], context.context);
+
+        let context = DateContext::from_entry(&entry("brunch, Birthday"), &previous);
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 4), context.date);
+        assert_eq!(TimePeriod::Afternoon, context.time);
+        assert_eq!(vec!["brunch", "birthday"], context.context);
+    }
+
+    #[test]
+    fn test_date_context_year_rollover() {
+        let test = |previous: (i32, u32, u32), date: &str, expected: (i32, u32, u32)| {
+            let entry = RawEntry {
+                date: Some(date.into()),
+                quantity: None,
+                name: None,
+                abv: None,
+                volume: None,
+            };
+
+            let context = DateContext::from_entry(&entry, &make_date_context(previous));
+            assert_eq!(
+                NaiveDate::from_ymd(expected.0, expected.1, expected.2),
+                context.date
+            );
+        };
+
+        // December into January, without an entry on New Year's Day.
+        test((2019, 12, 30), "5 jan", (2020, 1, 5));
+        test((2019, 12, 31), "1 jan", (2020, 1, 1));
+        test((2019, 11, 20), "feb 2", (2020, 2, 2));
+
+        // Later dates in the same year, including a second entry on New Year's Day.
+        test((2020, 1, 1), "1 jan", (2020, 1, 1));
+        test((2020, 1, 20), "2 feb", (2020, 2, 2));
+        test((2020, 6, 30), "1 jul", (2020, 7, 1));
+
+        // Slightly out of order within the same month.
+        test((2020, 10, 5), "3 oct", (2020, 10, 3));
+
+        // An explicit year is used as given.
+        test((2020, 10, 5), "3 oct 2018", (2018, 10, 3));
+        test((2019, 12, 30), "jan 5 2019, pub", (2019, 1, 5));
+    }
+
+    #[test]
+    fn test_date_context_into_previous() {
+        let entry = |date: Option<&str>| RawEntry {
+            date: date.map(|d| d.into()),
+            quantity: None,
+            name: None,
+            abv: None,
+            volume: None,
+        };
+
+        let context =
+            DateContext::from_entry(&entry(Some("5 may, pub")), &make_date_context((2020, 5, 4)));
+        let previous = context.into_previous();
+        assert_eq!(context.date, previous.date);
+        assert_eq!(context.time, previous.time);
+        assert!(previous.context.is_empty());
+
+        // An entry without a date uses the previous date and time, but not its context.
+        let next = DateContext::from_entry(&entry(None), &previous);
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 5), next.date);
+        assert!(next.context.is_empty());
+    }
+
+    #[test]
+    fn test_detect_year_rollover() {
+        let date = |y, m, d| NaiveDate::from_ymd(y, m, d);
+
+        let single_year = vec![date(2020, 1, 1), date(2020, 6, 1), date(2020, 12, 31)];
+        assert!(detect_year_rollover(&single_year).is_empty());
+
+        let dates = vec![
+            date(2019, 12, 30),
+            date(2019, 12, 31),
+            date(2020, 1, 1),
+            date(2020, 1, 1),
+            date(2020, 12, 31),
+            date(2021, 1, 2),
+        ];
+        assert_eq!(vec![2, 5], detect_year_rollover(&dates));
+
+        assert!(detect_year_rollover(&[]).is_empty());
+        assert!(detect_year_rollover(&[date(2020, 1, 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_date_context_serialization() {
+        let context = DateContext {
+            date: NaiveDate::from_ymd(2020, 5, 4),
+            time: TimePeriod::Evening,
+            context: vec!["pub".into(), "birthday".into()],
+        };
+
+        let json = serde_json::to_string(&context).unwrap();
+        assert_eq!(
+            r#"{"date":"2020-05-04","time":"Evening","context":["pub","birthday"]}"#,
+            json
+        );
+        assert_eq!(context, serde_json::from_str::<DateContext>(&json).unwrap());
+    }
+
+    #[test]
+    fn test_date_context_week_boundaries() {
+        let test = |date: (i32, u32, u32), start: (i32, u32, u32), end: (i32, u32, u32)| {
+            let context = make_date_context(date);
+            assert_eq!(
+                NaiveDate::from_ymd(start.0, start.1, start.2),
+                context.week_start()
+            );
+            assert_eq!(NaiveDate::from_ymd(end.0, end.1, end.2), context.week_end());
+        };
+
+        test((2020, 5, 6), (2020, 5, 4), (2020, 5, 10));
+        test((2020, 5, 4), (2020, 5, 4), (2020, 5, 10));
+        test((2020, 5, 10), (2020, 5, 4), (2020, 5, 10));
+
+        // 30 Dec 2019 is in the first ISO week of 2020.
+        test((2019, 12, 30), (2019, 12, 30), (2020, 1, 5));
+        test((2020, 1, 1), (2019, 12, 30), (2020, 1, 5));
+
+        // 1 Jan 2021 is in the last ISO week (53) of 2020.
+        test((2021, 1, 1), (2020, 12, 28), (2021, 1, 3));
+        test((2021, 1, 4), (2021, 1, 4), (2021, 1, 10));
+    }
+
+    #[test]
+    fn test_date_context_month_boundaries() {
+        let test = |date: (i32, u32, u32), start: (i32, u32, u32), end: (i32, u32, u32)| {
+            let context = make_date_context(date);
+            assert_eq!(
+                NaiveDate::from_ymd(start.0, start.1, start.2),
+                context.month_start()
+            );
+            assert_eq!(
+                NaiveDate::from_ymd(end.0, end.1, end.2),
+                context.month_end()
+            );
+        };
+
+        test((2020, 5, 6), (2020, 5, 1), (2020, 5, 31));
+        test((2020, 4, 30), (2020, 4, 1), (2020, 4, 30));
+        test((2020, 2, 10), (2020, 2, 1), (2020, 2, 29));
+        test((2021, 2, 10), (2021, 2, 1), (2021, 2, 28));
+        test((2019, 12, 31), (2019, 12, 1), (2019, 12, 31));
+    }
+
+    fn make_date_context(date: (i32, u32, u32)) -> DateContext {
+        DateContext {
+            date: NaiveDate::from_ymd(date.0, date.1, date.2),
+            time: TimePeriod::Evening,
+            context: vec![],
+        }
+    }
+
     fn make_quantity_entry(quantity: &str) -> RawEntry {
         RawEntry {
             date: None,
@@ -633,10 +1804,8 @@ mod tests {
         let (apprx_min, min, apprx_max, max) = tuple;
 
         QuantityRange {
-            min,
-            max,
-            approximate_min: apprx_min,
-            approximate_max: apprx_max,
+            min: ApproxF32::new(min, apprx_min),
+            max: ApproxF32::new(max, apprx_max),
         }
     }
 
@@ -644,10 +1813,8 @@ mod tests {
         let (apprx_min, min, apprx_max, max) = tuple;
 
         Abv {
-            min,
-            max,
-            approximate_min: apprx_min,
-            approximate_max: apprx_max,
+            min: ApproxF32::new(min, apprx_min),
+            max: ApproxF32::new(max, apprx_max),
         }
     }
 }
@@ -0,0 +1,44 @@
+/// A line which could not be imported, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    /// The 1-based number of the line within the imported file.
+    pub line_number: usize,
+
+    /// The line exactly as it was read.
+    pub raw_line: String,
+
+    /// A description of the problem with the line.
+    pub reason: String,
+}
+
+impl ImportError {
+    pub fn new<S: Into<String>>(line_number: usize, raw_line: String, reason: S) -> ImportError {
+        ImportError {
+            line_number,
+            raw_line,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {}: '{}' ({})",
+            self.line_number, self.raw_line, self.reason
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportError;
+
+    #[test]
+    fn test_import_error_display() {
+        let error = ImportError::new(3, "2,beer,150%".into(), "Invalid ABV");
+
+        assert_eq!("Line 3: '2,beer,150%' (Invalid ABV)", error.to_string());
+    }
+}
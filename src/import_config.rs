@@ -0,0 +1,111 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+/// Settings controlling how raw entries are interpreted during import.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ImportConfig {
+    /// Words in a drink name which indicate a double pour.
+    pub double_keywords: Vec<String>,
+
+    /// Words in a drink name which indicate a triple pour.
+    pub triple_keywords: Vec<String>,
+
+    /// Words in a drink name which indicate a half pour.
+    pub half_keywords: Vec<String>,
+}
+
+impl ImportConfig {
+    /// Load a configuration from a JSON file. Any missing fields use the default values.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<ImportConfig> {
+        let reader = BufReader::new(File::open(path)?);
+
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Get the serving multiplier indicated by the keywords in a drink `name`.
+    ///
+    /// Keywords only match whole words, so "Doubletree Ale" is not a double.
+    pub fn multiplier(&self, name: &str) -> f32 {
+        let words = split_words(name);
+        let matches = |keywords: &[String]| {
+            keywords.iter().any(|keyword| {
+                let keyword = split_words(keyword);
+                !keyword.is_empty()
+                    && words
+                        .windows(keyword.len())
+                        .any(|window| window == keyword.as_slice())
+            })
+        };
+
+        if matches(&self.triple_keywords) {
+            3.0
+        } else if matches(&self.double_keywords) {
+            2.0
+        } else if matches(&self.half_keywords) {
+            0.5
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Split `text` into lowercase words, ignoring any punctuation or whitespace between them.
+fn split_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+impl Default for ImportConfig {
+    fn default() -> ImportConfig {
+        ImportConfig {
+            double_keywords: vec!["double".into(), "dbl".into()],
+            triple_keywords: vec!["triple".into()],
+            half_keywords: vec!["half".into()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportConfig;
+
+    #[test]
+    fn test_default_multiplier() {
+        let config = ImportConfig::default();
+
+        assert_eq!(1.0, config.multiplier("whiskey"));
+        assert_eq!(2.0, config.multiplier("double whiskey"));
+        assert_eq!(2.0, config.multiplier("Dbl Whiskey"));
+        assert_eq!(3.0, config.multiplier("triple whiskey"));
+        assert_eq!(0.5, config.multiplier("half pint of cider"));
+    }
+
+    #[test]
+    fn test_partial_config() {
+        let config: ImportConfig = serde_json::from_str(r#"{"double_keywords": ["2x"]}"#).unwrap();
+
+        assert_eq!(2.0, config.multiplier("2x gin"));
+        assert_eq!(1.0, config.multiplier("double gin"));
+        assert_eq!(3.0, config.multiplier("triple gin"));
+    }
+
+    #[test]
+    fn test_multiplier_whole_words() {
+        let config = ImportConfig::default();
+
+        assert_eq!(1.0, config.multiplier("Doubletree Ale"));
+        assert_eq!(1.0, config.multiplier("Tripleroot Stout"));
+        assert_eq!(2.0, config.multiplier("Whiskey (double)"));
+        assert_eq!(0.5, config.multiplier("Cider, half"));
+
+        let config: ImportConfig =
+            serde_json::from_str(r#"{"half_keywords": ["half pint"]}"#).unwrap();
+
+        assert_eq!(0.5, config.multiplier("Half Pint of Stout"));
+        assert_eq!(1.0, config.multiplier("Half Acre Pale Ale"));
+    }
+}
@@ -11,9 +11,12 @@ extern crate serde;
 extern crate derive_more;
 
 pub mod api;
+pub mod auth;
 pub mod db;
 pub mod error;
+pub mod export;
 pub mod import;
+pub mod import_config;
 pub mod models;
 pub mod reports;
 pub mod schema;
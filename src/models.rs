@@ -1,3 +1,5 @@
+use crate::import;
+use crate::import::Abv;
 use crate::schema::*;
 use chrono::naive::NaiveDate;
 use chrono::{DateTime, Utc};
@@ -5,15 +7,17 @@ use diesel::deserialize::{self, FromSql};
 use diesel::pg::Pg;
 use diesel::serialize::{self, IsNull, Output, ToSql, WriteTuple};
 use diesel::sql_types::{Bool, Float4, Record};
+use serde::de::{self, Deserialize, Deserializer};
 use serde::Serialize;
+use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 use std::io::Write;
 use uom::si::f32::Volume as SiVolume;
 
 /// What percentage +/- should be applied to approximate values.
-static APPROX_MODIFIER: f32 = 0.1;
+pub(crate) static APPROX_MODIFIER: f32 = 0.1;
 
-#[derive(Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize, PartialEq, QueryId)]
+#[derive(Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize, Deserialize, QueryId)]
 #[sql_type = "Realapprox"]
 pub struct ApproxF32 {
     pub num: f32,
@@ -28,8 +32,9 @@ impl ApproxF32 {
         }
     }
 
+    /// The smallest this value could be, if it is approximate.
     #[inline]
-    pub fn min(&self) -> f32 {
+    pub fn lower_bound(&self) -> f32 {
         // This is a (probably dumb, unnecessary) attempt to avoid a conditional
         // so as to just use pure math operations.
         // In pseudocode, this is: `abv.is_approximate ? abv.num * (1 - MOD) : abv.num`.
@@ -39,8 +44,9 @@ impl ApproxF32 {
                     + ((!self.is_approximate as i32) as f32 * -1.0 * APPROX_MODIFIER)))
     }
 
+    /// The largest this value could be, if it is approximate.
     #[inline]
-    pub fn max(&self) -> f32 {
+    pub fn upper_bound(&self) -> f32 {
         // This is a (probably dumb, unnecessary) attempt to avoid a conditional
         // so as to just use pure math operations.
         // In pseudocode, this is: `abv.is_approximate ? abv.num * (1 + MOD) : abv.num`.
@@ -54,6 +60,20 @@ impl ApproxF32 {
     pub fn increment(&mut self) {
         self.num = self.num + 1.0;
     }
+
+    /// Restrict the number to within `[min_val, max_val]`, keeping the same approximation.
+    ///
+    /// This takes `self` by value, so that it is chosen over `Ord::clamp`.
+    pub fn clamp(self, min_val: f32, max_val: f32) -> ApproxF32 {
+        ApproxF32::new(self.num.max(min_val).min(max_val), self.is_approximate)
+    }
+
+    /// Compare with `other`, allowing the numbers to differ by less than `epsilon`.
+    ///
+    /// Useful when values may have picked up rounding errors, such as during unit conversion.
+    pub fn approximately_equals(&self, other: &ApproxF32, epsilon: f32) -> bool {
+        (self.num - other.num).abs() < epsilon && self.is_approximate == other.is_approximate
+    }
 }
 
 impl Hash for ApproxF32 {
@@ -63,7 +83,49 @@ impl Hash for ApproxF32 {
     }
 }
 
-#[derive(Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize)]
+/// Consistent with `Ord`, values are equal when both their numbers and approximations are.
+/// Unlike a bare `f32`, NaN is equal to itself.
+impl PartialEq for ApproxF32 {
+    fn eq(&self, other: &ApproxF32) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ApproxF32 {}
+
+/// Like `f32`, a NaN can't be compared with a number, so that gives `None`.
+/// Otherwise, this is the same as `cmp`.
+impl PartialOrd for ApproxF32 {
+    fn partial_cmp(&self, other: &ApproxF32) -> Option<Ordering> {
+        if self.num.is_nan() != other.num.is_nan() {
+            return None;
+        }
+
+        Some(self.cmp(other))
+    }
+}
+
+/// Values are ordered by their number, with exact values before approximate ones
+/// of the same number. NaN is equal to itself, and ordered after every other number.
+///
+/// Operators such as `<`, and `slice::sort`, use `partial_cmp`; values which may be NaN
+/// must be ordered with `cmp` directly, ex: `sort_by(Ord::cmp)`.
+impl Ord for ApproxF32 {
+    fn cmp(&self, other: &ApproxF32) -> Ordering {
+        self.num
+            .partial_cmp(&other.num)
+            .unwrap_or_else(|| self.num.is_nan().cmp(&other.num.is_nan()))
+            .then(self.is_approximate.cmp(&other.is_approximate))
+    }
+}
+
+/// The part of a day in which a drink was drank.
+///
+/// Time periods are ordered chronologically, from `Morning` through `Night`,
+/// so the variants must stay in that order.
+#[derive(
+    Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize, PartialEq, Eq, PartialOrd, Ord,
+)]
 #[sql_type = "Timeperiod"]
 pub enum TimePeriod {
     Morning,
@@ -72,7 +134,7 @@ pub enum TimePeriod {
     Night,
 }
 
-#[derive(Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize)]
+#[derive(Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize, PartialEq)]
 #[sql_type = "Volumeunit"]
 #[allow(non_camel_case_types)]
 pub enum VolumeUnit {
@@ -80,6 +142,14 @@ pub enum VolumeUnit {
     mL,
     cL,
     L,
+    /// Imperial pint (568 mL), as served in the UK and Ireland.
+    UkPint,
+    /// US liquid pint (473 mL).
+    UsPint,
+    /// US liquid gallon (3.785 L).
+    Gallon,
+    /// US liquid quart (946 mL).
+    Quart,
 }
 
 #[derive(Clone, Copy, Debug, FromSqlRow, AsExpression, Serialize)]
@@ -89,15 +159,32 @@ pub struct LiquidVolume {
     pub unit: VolumeUnit,
 }
 
+impl Eq for VolumeUnit {}
+
+impl Hash for VolumeUnit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (*self as u8).hash(state);
+    }
+}
+
+/// How many mL make up one imperial pint.
+const ML_PER_UK_PINT: f32 = 568.261;
+
 impl LiquidVolume {
     pub fn to_si_volume(&self) -> SiVolume {
-        use uom::si::volume::{centiliter, fluid_ounce, liter, milliliter};
+        use uom::si::volume::{
+            centiliter, fluid_ounce, gallon, liter, milliliter, pint_liquid, quart_liquid,
+        };
 
         match self.unit {
             VolumeUnit::FlOz => SiVolume::new::<fluid_ounce>(self.amount.num),
             VolumeUnit::mL => SiVolume::new::<milliliter>(self.amount.num),
             VolumeUnit::cL => SiVolume::new::<centiliter>(self.amount.num),
             VolumeUnit::L => SiVolume::new::<liter>(self.amount.num),
+            VolumeUnit::UkPint => SiVolume::new::<milliliter>(self.amount.num * ML_PER_UK_PINT),
+            VolumeUnit::UsPint => SiVolume::new::<pint_liquid>(self.amount.num),
+            VolumeUnit::Gallon => SiVolume::new::<gallon>(self.amount.num),
+            VolumeUnit::Quart => SiVolume::new::<quart_liquid>(self.amount.num),
         }
     }
 
@@ -113,6 +200,77 @@ impl LiquidVolume {
             amount: amount,
         }
     }
+
+    /// Convert this volume to the given `unit`, keeping the same approximation.
+    pub fn convert_to(&self, unit: VolumeUnit) -> LiquidVolume {
+        use uom::si::volume::{
+            centiliter, fluid_ounce, gallon, liter, milliliter, pint_liquid, quart_liquid,
+        };
+
+        let volume = self.to_si_volume();
+        let num = match unit {
+            VolumeUnit::FlOz => volume.get::<fluid_ounce>(),
+            VolumeUnit::mL => volume.get::<milliliter>(),
+            VolumeUnit::cL => volume.get::<centiliter>(),
+            VolumeUnit::L => volume.get::<liter>(),
+            VolumeUnit::UkPint => volume.get::<milliliter>() / ML_PER_UK_PINT,
+            VolumeUnit::UsPint => volume.get::<pint_liquid>(),
+            VolumeUnit::Gallon => volume.get::<gallon>(),
+            VolumeUnit::Quart => volume.get::<quart_liquid>(),
+        };
+
+        LiquidVolume {
+            amount: ApproxF32::new(num, self.amount.is_approximate),
+            unit,
+        }
+    }
+
+    /// The volume in hundredths of a mL, for the purposes of comparison and hashing.
+    fn ml_key(&self) -> i64 {
+        (self.to_ml().amount.num * 100.0).round() as i64
+    }
+}
+
+/// Volumes are compared by converting both to mL, so values in different units
+/// which represent the same volume will compare as equal; ex: 330 mL == 33 cL.
+impl PartialEq for LiquidVolume {
+    fn eq(&self, other: &LiquidVolume) -> bool {
+        self.ml_key() == other.ml_key()
+    }
+}
+
+impl Eq for LiquidVolume {}
+
+/// Consistent with `PartialEq`, volumes are hashed by their amount in mL,
+/// so equal volumes in different units hash equally.
+impl Hash for LiquidVolume {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ml_key().hash(state);
+    }
+}
+
+/// Volumes are added in the unit of the left-hand side.
+impl std::ops::Add for LiquidVolume {
+    type Output = LiquidVolume;
+
+    fn add(self, other: LiquidVolume) -> LiquidVolume {
+        let other = other.convert_to(self.unit);
+
+        LiquidVolume {
+            amount: ApproxF32::new(
+                self.amount.num + other.amount.num,
+                self.amount.is_approximate || other.amount.is_approximate,
+            ),
+            unit: self.unit,
+        }
+    }
+}
+
+/// Volumes are ordered by converting both to mL.
+impl PartialOrd for LiquidVolume {
+    fn partial_cmp(&self, other: &LiquidVolume) -> Option<Ordering> {
+        Some(self.ml_key().cmp(&other.ml_key()))
+    }
 }
 
 impl TimePeriod {
@@ -147,6 +305,15 @@ impl std::fmt::Display for TimePeriod {
     }
 }
 
+/// Accepts both the serialized variant name ("Evening") and the lowercase form ("evening").
+impl<'de> Deserialize<'de> for TimePeriod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let time = String::deserialize(deserializer)?;
+        TimePeriod::from_str(&time.to_lowercase())
+            .ok_or_else(|| de::Error::custom(format!("unrecognized time period '{}'", time)))
+    }
+}
+
 impl ToSql<Realapprox, Pg> for ApproxF32 {
     fn to_sql<W: Write>(&self, out: &mut Output<W, Pg>) -> serialize::Result {
         WriteTuple::<(Float4, Bool)>::write_tuple(&(self.num, self.is_approximate), out)
@@ -189,6 +356,11 @@ impl VolumeUnit {
             "ml" => Some(VolumeUnit::mL),
             "cl" => Some(VolumeUnit::cL),
             "l" => Some(VolumeUnit::L),
+            "uk pint" | "imperial pint" => Some(VolumeUnit::UkPint),
+            // A bare "pint" is ambiguous; assume US unless told otherwise.
+            "us pint" | "pint" | "pints" | "pt" => Some(VolumeUnit::UsPint),
+            "gallon" | "gallons" | "gal" => Some(VolumeUnit::Gallon),
+            "quart" | "quarts" | "qt" => Some(VolumeUnit::Quart),
             _ => None,
         }
     }
@@ -199,8 +371,35 @@ impl VolumeUnit {
             VolumeUnit::mL => "mL",
             VolumeUnit::cL => "cL",
             VolumeUnit::L => "L",
+            VolumeUnit::UkPint => "uk pint",
+            VolumeUnit::UsPint => "us pint",
+            VolumeUnit::Gallon => "gallon",
+            VolumeUnit::Quart => "quart",
         }
     }
+
+    /// Returns whether this unit is one of the pint variants.
+    pub fn is_pint(&self) -> bool {
+        match self {
+            VolumeUnit::UkPint | VolumeUnit::UsPint => true,
+            _ => false,
+        }
+    }
+}
+
+/// Accepts both the serialized variant name ("FlOz") and the forms accepted by `from_str`.
+impl<'de> Deserialize<'de> for VolumeUnit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let unit = String::deserialize(deserializer)?;
+        let variant = match unit.as_str() {
+            "FlOz" => Some(VolumeUnit::FlOz),
+            "UkPint" => Some(VolumeUnit::UkPint),
+            "UsPint" => Some(VolumeUnit::UsPint),
+            _ => VolumeUnit::from_str(&unit),
+        };
+
+        variant.ok_or_else(|| de::Error::custom(format!("unrecognized volume unit '{}'", unit)))
+    }
 }
 
 impl ToSql<Volumeunit, Pg> for VolumeUnit {
@@ -210,6 +409,10 @@ impl ToSql<Volumeunit, Pg> for VolumeUnit {
             VolumeUnit::mL => out.write_all(b"mL")?,
             VolumeUnit::cL => out.write_all(b"cL")?,
             VolumeUnit::L => out.write_all(b"L")?,
+            VolumeUnit::UkPint => out.write_all(b"uk pint")?,
+            VolumeUnit::UsPint => out.write_all(b"us pint")?,
+            VolumeUnit::Gallon => out.write_all(b"gallon")?,
+            VolumeUnit::Quart => out.write_all(b"quart")?,
         }
         Ok(IsNull::No)
     }
@@ -222,6 +425,10 @@ impl FromSql<Volumeunit, Pg> for VolumeUnit {
             b"mL" => Ok(VolumeUnit::mL),
             b"cL" => Ok(VolumeUnit::cL),
             b"L" => Ok(VolumeUnit::L),
+            b"uk pint" => Ok(VolumeUnit::UkPint),
+            b"us pint" => Ok(VolumeUnit::UsPint),
+            b"gallon" => Ok(VolumeUnit::Gallon),
+            b"quart" => Ok(VolumeUnit::Quart),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -249,7 +456,8 @@ impl FromSql<Volume, Pg> for LiquidVolume {
     }
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, Identifiable)]
+#[table_name = "entry"]
 pub struct PlainEntry {
     pub id: i32,
     pub person_id: i32,
@@ -282,7 +490,29 @@ pub struct NewEntry<'a> {
     pub volume_ml: Option<LiquidVolume>,
 }
 
-#[derive(Queryable, Debug)]
+/// A record of a single field of an entry being changed.
+#[derive(Queryable, Clone, Debug, Serialize)]
+pub struct AuditLogEntry {
+    pub id: i32,
+    pub entry_id: i32,
+    pub changed_at: DateTime<Utc>,
+    pub field_name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by: i32,
+}
+
+#[derive(Insertable)]
+#[table_name = "entry_audit_log"]
+pub struct NewAuditLogEntry<'a> {
+    pub entry_id: i32,
+    pub field_name: &'a str,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by: i32,
+}
+
+#[derive(Queryable, Clone, Debug, Serialize)]
 pub struct Drink {
     pub id: i32,
     pub name: String,
@@ -295,6 +525,31 @@ pub struct Drink {
     pub updated_at: DateTime<Utc>,
 }
 
+impl Drink {
+    /// The drink's name, along with its ABV if known; ex: "pale ale (5.0%)".
+    pub fn display_name(&self) -> String {
+        match Abv::from_approx_f32_pair(self.min_abv, self.max_abv) {
+            Some(abv) => format!("{} ({})", self.name, abv),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Drink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Person {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub username: Option<String>,
+    pub password_hash: Option<String>,
+}
+
 #[derive(Insertable)]
 #[table_name = "drink"]
 pub struct NewDrink<'a> {
@@ -303,3 +558,270 @@ pub struct NewDrink<'a> {
     pub max_abv: Option<ApproxF32>,
     pub multiplier: f32,
 }
+
+impl<'a> From<&'a import::Drink> for NewDrink<'a> {
+    fn from(drink: &'a import::Drink) -> NewDrink<'a> {
+        NewDrink {
+            name: drink.name.as_str(),
+            min_abv: drink.abv.as_ref().map(|abv| abv.min),
+            max_abv: drink.abv.as_ref().map(|abv| abv.max),
+            multiplier: drink.multiplier,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(amount: f32, unit: VolumeUnit) -> LiquidVolume {
+        LiquidVolume {
+            amount: ApproxF32::new(amount, false),
+            unit,
+        }
+    }
+
+    #[test]
+    fn test_approx_f32_clamp() {
+        let clamp = |num, is_approximate| ApproxF32::new(num, is_approximate).clamp(0.0, 100.0);
+
+        assert_eq!(ApproxF32::new(5.0, true), clamp(5.0, true));
+        assert_eq!(ApproxF32::new(0.0, false), clamp(0.0, false));
+        assert_eq!(ApproxF32::new(100.0, true), clamp(100.0, true));
+        assert_eq!(ApproxF32::new(100.0, true), clamp(100.5, true));
+        assert_eq!(ApproxF32::new(0.0, false), clamp(-0.1, false));
+    }
+
+    #[test]
+    fn test_approx_f32_ordering() {
+        let exact = ApproxF32::new(5.0, false);
+        let approximate = ApproxF32::new(5.0, true);
+
+        assert!(ApproxF32::new(4.0, true) < exact);
+        assert!(exact < ApproxF32::new(6.0, false));
+        assert!(exact < approximate);
+        assert_eq!(Some(Ordering::Less), exact.partial_cmp(&approximate));
+        assert_eq!(Ordering::Greater, approximate.cmp(&exact));
+        assert_eq!(Ordering::Equal, exact.cmp(&ApproxF32::new(5.0, false)));
+
+        let values = vec![approximate, ApproxF32::new(1.5, true), exact];
+        assert_eq!(Some(&approximate), values.iter().max());
+        assert_eq!(Some(&ApproxF32::new(1.5, true)), values.iter().min());
+    }
+
+    #[test]
+    fn test_approx_f32_ordering_nan() {
+        let nan = ApproxF32::new(std::f32::NAN, false);
+        let number = ApproxF32::new(5.0, false);
+
+        assert_eq!(Ordering::Greater, nan.cmp(&number));
+        assert_eq!(Ordering::Less, number.cmp(&nan));
+        assert_eq!(Ordering::Equal, nan.cmp(&nan));
+
+        assert_eq!(None, nan.partial_cmp(&number));
+        assert_eq!(None, number.partial_cmp(&nan));
+
+        assert_eq!(nan, nan);
+        assert_ne!(nan, number);
+        assert_ne!(nan, ApproxF32::new(std::f32::NAN, true));
+        assert_eq!(Some(Ordering::Equal), nan.partial_cmp(&nan));
+
+        let mut values = vec![nan, number, ApproxF32::new(1.0, true)];
+        values.sort_by(Ord::cmp);
+        assert_eq!(1.0, values[0].num);
+        assert_eq!(5.0, values[1].num);
+        assert!(values[2].num.is_nan());
+    }
+
+    #[test]
+    fn test_liquid_volume_eq() {
+        assert_eq!(volume(330.0, VolumeUnit::mL), volume(33.0, VolumeUnit::cL));
+        assert_eq!(volume(1.0, VolumeUnit::L), volume(100.0, VolumeUnit::cL));
+        assert_eq!(volume(1.0, VolumeUnit::L), volume(1000.0, VolumeUnit::mL));
+        assert_eq!(volume(1.0, VolumeUnit::Gallon), volume(4.0, VolumeUnit::Quart));
+        assert_eq!(volume(1.0, VolumeUnit::Quart), volume(2.0, VolumeUnit::UsPint));
+        assert_ne!(
+            volume(330.0, VolumeUnit::mL),
+            volume(12.0, VolumeUnit::FlOz)
+        );
+
+        // Whether a volume is approximate has no bearing on equality.
+        let mut approximate = volume(330.0, VolumeUnit::mL);
+        approximate.amount.is_approximate = true;
+        assert_eq!(volume(33.0, VolumeUnit::cL), approximate);
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_liquid_volume_hash() {
+        use std::collections::HashSet;
+
+        assert_eq!(
+            hash_of(&volume(330.0, VolumeUnit::mL)),
+            hash_of(&volume(330.0, VolumeUnit::mL))
+        );
+        assert_eq!(
+            hash_of(&volume(330.0, VolumeUnit::mL)),
+            hash_of(&volume(33.0, VolumeUnit::cL))
+        );
+        assert_ne!(
+            hash_of(&volume(330.0, VolumeUnit::mL)),
+            hash_of(&volume(500.0, VolumeUnit::mL))
+        );
+        assert_eq!(hash_of(&VolumeUnit::FlOz), hash_of(&VolumeUnit::FlOz));
+        assert_ne!(hash_of(&VolumeUnit::FlOz), hash_of(&VolumeUnit::mL));
+
+        let sizes: HashSet<LiquidVolume> = vec![
+            volume(330.0, VolumeUnit::mL),
+            volume(33.0, VolumeUnit::cL),
+            volume(1.0, VolumeUnit::L),
+            volume(1000.0, VolumeUnit::mL),
+            volume(12.0, VolumeUnit::FlOz),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(3, sizes.len());
+
+        // Volumes which are equal, but not identical, must also hash equally.
+        let a = volume(329.996, VolumeUnit::mL);
+        let b = volume(330.004, VolumeUnit::mL);
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+
+    #[test]
+    fn test_liquid_volume_add() {
+        let total = volume(330.0, VolumeUnit::mL) + volume(50.0, VolumeUnit::cL);
+        assert_eq!(VolumeUnit::mL, total.unit);
+        assert_eq!(volume(830.0, VolumeUnit::mL), total);
+
+        let approximate = LiquidVolume {
+            amount: ApproxF32::new(500.0, true),
+            unit: VolumeUnit::mL,
+        };
+        let total = volume(1.0, VolumeUnit::L) + approximate;
+        assert_eq!(VolumeUnit::L, total.unit);
+        assert!((total.amount.num - 1.5).abs() < 0.0001);
+        assert!(total.amount.is_approximate);
+    }
+
+    #[test]
+    fn test_liquid_volume_ord() {
+        // 12 fl oz is roughly 354.9 mL.
+        assert!(volume(12.0, VolumeUnit::FlOz) > volume(350.0, VolumeUnit::mL));
+        assert!(volume(12.0, VolumeUnit::FlOz) < volume(355.0, VolumeUnit::mL));
+        assert!(volume(25.0, VolumeUnit::cL) < volume(330.0, VolumeUnit::mL));
+        assert!(volume(33.0, VolumeUnit::cL) <= volume(330.0, VolumeUnit::mL));
+        assert!(volume(33.0, VolumeUnit::cL) >= volume(330.0, VolumeUnit::mL));
+    }
+
+    #[test]
+    fn test_new_drink_from_import_drink() {
+        let stout = import::Drink {
+            name: "stout".into(),
+            abv: Some(Abv {
+                min: ApproxF32::new(4.2, false),
+                max: ApproxF32::new(4.5, true),
+            }),
+            multiplier: 2.0,
+        };
+
+        let new_drink = NewDrink::from(&stout);
+        assert_eq!("stout", new_drink.name);
+        assert_eq!(Some(ApproxF32::new(4.2, false)), new_drink.min_abv);
+        assert_eq!(Some(ApproxF32::new(4.5, true)), new_drink.max_abv);
+        assert_eq!(2.0, new_drink.multiplier);
+
+        let unknown_abv = import::Drink {
+            abv: None,
+            ..stout.clone()
+        };
+
+        let new_drink = NewDrink::from(&unknown_abv);
+        assert_eq!("stout", new_drink.name);
+        assert_eq!(None, new_drink.min_abv);
+        assert_eq!(None, new_drink.max_abv);
+        assert_eq!(2.0, new_drink.multiplier);
+    }
+
+    #[test]
+    fn test_time_period_serde_round_trip() {
+        let periods = [
+            TimePeriod::Morning,
+            TimePeriod::Afternoon,
+            TimePeriod::Evening,
+            TimePeriod::Night,
+        ];
+
+        for period in periods.iter() {
+            let json = serde_json::to_string(period).unwrap();
+            assert_eq!(format!("\"{:?}\"", period), json);
+            assert_eq!(*period, serde_json::from_str::<TimePeriod>(&json).unwrap());
+
+            let text = format!("\"{}\"", period.to_str());
+            assert_eq!(*period, serde_json::from_str::<TimePeriod>(&text).unwrap());
+        }
+
+        assert!(serde_json::from_str::<TimePeriod>("\"brunch\"").is_err());
+    }
+
+    #[test]
+    fn test_time_period_ordering() {
+        assert!(TimePeriod::Morning < TimePeriod::Afternoon);
+        assert!(TimePeriod::Afternoon < TimePeriod::Evening);
+        assert!(TimePeriod::Evening < TimePeriod::Night);
+        assert!(TimePeriod::Night > TimePeriod::Morning);
+        assert_eq!(TimePeriod::Evening, TimePeriod::Evening);
+        assert_eq!(Ordering::Equal, TimePeriod::Night.cmp(&TimePeriod::Night));
+
+        let mut periods = vec![
+            TimePeriod::Night,
+            TimePeriod::Afternoon,
+            TimePeriod::Morning,
+            TimePeriod::Evening,
+        ];
+        periods.sort();
+
+        assert_eq!(
+            vec![
+                TimePeriod::Morning,
+                TimePeriod::Afternoon,
+                TimePeriod::Evening,
+                TimePeriod::Night,
+            ],
+            periods
+        );
+    }
+
+    #[test]
+    fn test_volume_unit_serde_round_trip() {
+        let units = [
+            VolumeUnit::FlOz,
+            VolumeUnit::mL,
+            VolumeUnit::cL,
+            VolumeUnit::L,
+            VolumeUnit::UkPint,
+            VolumeUnit::UsPint,
+            VolumeUnit::Gallon,
+            VolumeUnit::Quart,
+        ];
+
+        for unit in units.iter() {
+            let json = serde_json::to_string(unit).unwrap();
+            assert_eq!(format!("\"{:?}\"", unit), json);
+            assert_eq!(*unit, serde_json::from_str::<VolumeUnit>(&json).unwrap());
+
+            let text = format!("\"{}\"", unit.to_str());
+            assert_eq!(*unit, serde_json::from_str::<VolumeUnit>(&text).unwrap());
+        }
+
+        assert!(serde_json::from_str::<VolumeUnit>("\"hogshead\"").is_err());
+    }
+}
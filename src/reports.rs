@@ -1,5 +1,7 @@
 use crate::db::Entry;
-use crate::models::LiquidVolume;
+use crate::models::{LiquidVolume, TimePeriod, VolumeUnit};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 #[derive(Serialize)]
 pub struct DrinkAggregate {
@@ -10,12 +12,93 @@ pub struct DrinkAggregate {
     pub max_volume: Option<LiquidVolume>,
 }
 
+impl DrinkAggregate {
+    /// An aggregate of no drinks, and no volume.
+    pub fn zero() -> DrinkAggregate {
+        DrinkAggregate {
+            min_drinks: 0.0,
+            max_drinks: 0.0,
+            min_volume: None,
+            max_volume: None,
+        }
+    }
+
+    /// The midpoint between the minimum and maximum number of drinks.
+    pub fn expected_drinks(&self) -> f32 {
+        (self.min_drinks + self.max_drinks) / 2.0
+    }
+}
+
+impl std::ops::Add for DrinkAggregate {
+    type Output = DrinkAggregate;
+
+    fn add(self, other: DrinkAggregate) -> DrinkAggregate {
+        let add_volumes = |a: Option<LiquidVolume>, b: Option<LiquidVolume>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+
+        DrinkAggregate {
+            min_drinks: self.min_drinks + other.min_drinks,
+            max_drinks: self.max_drinks + other.max_drinks,
+            min_volume: add_volumes(self.min_volume, other.min_volume),
+            max_volume: add_volumes(self.max_volume, other.max_volume),
+        }
+    }
+}
+
+impl std::iter::Sum for DrinkAggregate {
+    fn sum<I: Iterator<Item = DrinkAggregate>>(iter: I) -> DrinkAggregate {
+        iter.fold(DrinkAggregate::zero(), |total, aggregate| total + aggregate)
+    }
+}
+
+/// The amount of pure alcohol which makes up one "standard drink", which varies by country.
+#[derive(Clone, Copy, Debug)]
+pub struct DrinkStandard {
+    pub ml_per_alcohol: f32,
+}
+
+impl DrinkStandard {
+    /// One UK unit is 10 mL of pure alcohol.
+    pub const UK: DrinkStandard = DrinkStandard {
+        ml_per_alcohol: 10.0,
+    };
+
+    /// One US standard drink is 14 grams, or roughly 17.7 mL, of pure alcohol.
+    pub const US: DrinkStandard = DrinkStandard {
+        ml_per_alcohol: 17.7,
+    };
+
+    /// Get the standard for the given unit `system`, either "uk" or "us".
+    pub fn from_system(system: &str) -> Option<DrinkStandard> {
+        match system.to_lowercase().as_str() {
+            "uk" => Some(DrinkStandard::UK),
+            "us" => Some(DrinkStandard::US),
+            _ => None,
+        }
+    }
+}
+
+/// A standard drink is 14 mL of pure alcohol by default.
+impl Default for DrinkStandard {
+    fn default() -> DrinkStandard {
+        DrinkStandard {
+            ml_per_alcohol: 14.0,
+        }
+    }
+}
+
 pub trait DrinkAggregator {
-    fn aggregate(&self) -> DrinkAggregate;
+    fn aggregate_with_standard(&self, standard: &DrinkStandard) -> DrinkAggregate;
+
+    fn aggregate(&self) -> DrinkAggregate {
+        self.aggregate_with_standard(&DrinkStandard::default())
+    }
 }
 
 impl DrinkAggregator for Entry {
-    fn aggregate(&self) -> DrinkAggregate {
+    fn aggregate_with_standard(&self, standard: &DrinkStandard) -> DrinkAggregate {
         // If there is no ABV information, then we'll just assume
         // that each "unit" is 1 drink (times the multiplier).
         if !self.has_abv() || !self.has_volume() {
@@ -35,28 +118,2212 @@ impl DrinkAggregator for Entry {
             };
         }
 
-        let min_abv = self.min_abv().expect("Missing min ABV value!");
-        let max_abv = self.max_abv().expect("Missing max ABV value!");
+        let abv = self.abv().expect("Missing ABV value!");
+        let (min_abv, max_abv) = (abv.min.lower_bound(), abv.max.upper_bound());
         let volume_ml = self.volume_ml.expect("Missing volume!");
 
         // How many mL of alcohol constitute 1 drink.
-        let ml_per_drink = 18.0;
+        let ml_per_drink = standard.ml_per_alcohol;
 
         DrinkAggregate {
-            min_drinks: self.min_quantity() * (min_abv / 100.0) * volume_ml.amount.min()
+            min_drinks: self.min_quantity() * (min_abv / 100.0) * volume_ml.amount.lower_bound()
                 / ml_per_drink,
-            max_drinks: self.max_quantity() * (max_abv / 100.0) * volume_ml.amount.max()
+            max_drinks: self.max_quantity() * (max_abv / 100.0) * volume_ml.amount.upper_bound()
                 / ml_per_drink,
             min_volume: self.volume.map(|v| {
                 let mut vol = v.clone();
-                vol.amount.num = vol.amount.min() * self.min_quantity() * self.multiplier;
+                vol.amount.num = vol.amount.lower_bound() * self.min_quantity() * self.multiplier;
                 vol
             }),
             max_volume: self.volume.map(|v| {
                 let mut vol = v.clone();
-                vol.amount.num = vol.amount.max() * self.max_quantity() * self.multiplier;
+                vol.amount.num = vol.amount.upper_bound() * self.max_quantity() * self.multiplier;
                 vol
             }),
         }
     }
 }
+
+/// The total of every entry's aggregate.
+impl DrinkAggregator for [Entry] {
+    fn aggregate_with_standard(&self, standard: &DrinkStandard) -> DrinkAggregate {
+        self.iter()
+            .map(|entry| entry.aggregate_with_standard(standard))
+            .sum()
+    }
+}
+
+/// The total of every entry drank on a single day.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename = "daily_aggregate")]
+pub struct DailyAggregate {
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+
+    pub min_volume: Option<LiquidVolume>,
+    pub max_volume: Option<LiquidVolume>,
+}
+
+/// Sum the aggregates of the `entries` of a single day.
+pub fn aggregate_day(entries: &[Entry], standard: &DrinkStandard) -> DailyAggregate {
+    let total = entries.aggregate_with_standard(standard);
+
+    DailyAggregate {
+        min_drinks: total.min_drinks,
+        max_drinks: total.max_drinks,
+        min_volume: total.min_volume,
+        max_volume: total.max_volume,
+    }
+}
+
+/// Progress towards a weekly drinking limit.
+#[derive(Serialize, Debug)]
+#[serde(rename = "budget")]
+pub struct DrinkBudget {
+    pub weekly_limit: f32,
+    pub this_week_used_min: f32,
+    pub this_week_used_max: f32,
+
+    /// How many drinks are left before reaching the limit.
+    /// These will be negative if the limit has been exceeded.
+    pub remaining_min: f32,
+    pub remaining_max: f32,
+
+    pub on_track: bool,
+}
+
+impl DrinkBudget {
+    pub fn new(weekly_limit: f32, used_min: f32, used_max: f32) -> DrinkBudget {
+        DrinkBudget {
+            weekly_limit,
+            this_week_used_min: used_min,
+            this_week_used_max: used_max,
+            remaining_min: weekly_limit - used_max,
+            remaining_max: weekly_limit - used_min,
+            on_track: used_max <= weekly_limit,
+        }
+    }
+
+    /// Calculate the budget given this week's `entries`.
+    pub fn from_entries(
+        entries: &[Entry],
+        weekly_limit: f32,
+        standard: &DrinkStandard,
+    ) -> DrinkBudget {
+        let (used_min, used_max) = entries
+            .iter()
+            .map(|entry| entry.aggregate_with_standard(standard))
+            .fold((0.0, 0.0), |(min, max), aggregate| {
+                (min + aggregate.min_drinks, max + aggregate.max_drinks)
+            });
+
+        DrinkBudget::new(weekly_limit, used_min, used_max)
+    }
+}
+
+/// The total number of standard drinks across all entries with both ABV and volume information.
+#[derive(Serialize, Debug)]
+#[serde(rename = "weighted_units")]
+pub struct WeightedUnits {
+    pub system: String,
+    pub total_units_min: f32,
+    pub total_units_max: f32,
+
+    /// How many entries were included in the totals.
+    pub entries_with_data: u32,
+
+    /// How many entries were excluded for lack of ABV or volume information.
+    pub entries_without_data: u32,
+}
+
+impl WeightedUnits {
+    /// Sum the standard drinks of `entries` for the given unit `system`, either "uk" or "us".
+    pub fn from_entries(entries: &[Entry], system: &str) -> Option<WeightedUnits> {
+        let standard = DrinkStandard::from_system(system)?;
+
+        let (with_data, without_data): (Vec<&Entry>, Vec<&Entry>) = entries
+            .iter()
+            .partition(|entry| entry.has_abv() && entry.has_volume());
+
+        let (total_min, total_max) = with_data
+            .iter()
+            .map(|entry| entry.aggregate_with_standard(&standard))
+            .fold((0.0, 0.0), |(min, max), aggregate| {
+                (min + aggregate.min_drinks, max + aggregate.max_drinks)
+            });
+
+        Some(WeightedUnits {
+            system: system.to_lowercase(),
+            total_units_min: total_min,
+            total_units_max: total_max,
+            entries_with_data: with_data.len() as u32,
+            entries_without_data: without_data.len() as u32,
+        })
+    }
+}
+
+/// A period of time between two consecutive days with entries.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct EntryGap {
+    pub gap_start: NaiveDate,
+    pub gap_end: NaiveDate,
+    pub gap_days: u32,
+}
+
+/// Find every gap between consecutive `days` which is longer than `threshold_days`.
+///
+/// `days` are expected to be distinct and in ascending order.
+pub fn entry_gaps(days: &[NaiveDate], threshold_days: u32) -> Vec<EntryGap> {
+    days.windows(2)
+        .map(|pair| EntryGap {
+            gap_start: pair[0],
+            gap_end: pair[1],
+            gap_days: (pair[1] - pair[0]).num_days() as u32,
+        })
+        .filter(|gap| gap.gap_days > threshold_days)
+        .collect()
+}
+
+/// How many gaps between consecutive drinking days fell within a range of lengths.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FrequencyBucket {
+    pub gap_days_start: u32,
+    pub gap_days_end: u32,
+    pub count: u32,
+}
+
+/// Build a histogram of the gaps between consecutive days with entries, with each bucket
+/// covering `bucket_days` gap lengths, ex: 0-1, 2-3, 4-5 days when `bucket_days` is 2.
+///
+/// Buckets run from zero up to the longest gap, including any empty buckets in between.
+/// A `bucket_days` of zero is treated as one.
+pub fn entry_frequency_histogram(entries: &[Entry], bucket_days: u32) -> Vec<FrequencyBucket> {
+    let bucket_days = bucket_days.max(1);
+    let days: Vec<NaiveDate> = entries
+        .iter()
+        .map(|entry| entry.drank_on)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let gaps: Vec<u32> = days
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_days() as u32)
+        .collect();
+
+    let bucket_count = match gaps.iter().max() {
+        Some(longest) => longest / bucket_days + 1,
+        None => return Vec::new(),
+    };
+
+    let mut buckets: Vec<FrequencyBucket> = (0..bucket_count)
+        .map(|bucket| FrequencyBucket {
+            gap_days_start: bucket * bucket_days,
+            gap_days_end: bucket * bucket_days + bucket_days - 1,
+            count: 0,
+        })
+        .collect();
+
+    for gap in gaps {
+        buckets[(gap / bucket_days) as usize].count += 1;
+    }
+
+    buckets
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum StreakKind {
+    Drinking,
+    Sober,
+}
+
+/// A run of consecutive days which were either all drinking days or all sober days.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StreakPeriod {
+    pub kind: StreakKind,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: u32,
+}
+
+/// Split the days from the first entry through `today` into alternating
+/// drinking and sober periods, in chronological order.
+///
+/// Returns nothing if there are no entries.
+pub fn streak_history(entries: &[Entry], today: NaiveDate) -> Vec<StreakPeriod> {
+    let days: BTreeSet<NaiveDate> = entries
+        .iter()
+        .map(|entry| entry.drank_on)
+        .filter(|day| *day <= today)
+        .collect();
+
+    let period = |kind, start: NaiveDate, end: NaiveDate| StreakPeriod {
+        kind,
+        start,
+        end,
+        days: (end - start).num_days() as u32 + 1,
+    };
+
+    let mut history = Vec::new();
+    let mut streak: Option<(NaiveDate, NaiveDate)> = None;
+
+    for day in days {
+        streak = match streak {
+            Some((start, end)) if day == end.succ() => Some((start, day)),
+            Some((start, end)) => {
+                history.push(period(StreakKind::Drinking, start, end));
+                history.push(period(StreakKind::Sober, end.succ(), day.pred()));
+                Some((day, day))
+            }
+            None => Some((day, day)),
+        };
+    }
+
+    if let Some((start, end)) = streak {
+        history.push(period(StreakKind::Drinking, start, end));
+        if end < today {
+            history.push(period(StreakKind::Sober, end.succ(), today));
+        }
+    }
+
+    history
+}
+
+/// The current streak, along with the longest drinking and sober streaks.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StreakInfo {
+    pub current: Option<StreakPeriod>,
+    pub longest_drinking: Option<StreakPeriod>,
+    pub longest_sober: Option<StreakPeriod>,
+}
+
+/// Find the current and longest streaks from the first entry through `today`.
+/// When streaks are tied for the longest, the earliest is used.
+pub fn streak_info(entries: &[Entry], today: NaiveDate) -> StreakInfo {
+    let history = streak_history(entries, today);
+
+    let longest = |kind: StreakKind| {
+        history
+            .iter()
+            .filter(|period| period.kind == kind)
+            .fold(
+                None,
+                |longest: Option<&StreakPeriod>, period| match longest {
+                    Some(longest) if longest.days >= period.days => Some(longest),
+                    _ => Some(period),
+                },
+            )
+            .cloned()
+    };
+
+    StreakInfo {
+        current: history.last().cloned(),
+        longest_drinking: longest(StreakKind::Drinking),
+        longest_sober: longest(StreakKind::Sober),
+    }
+}
+
+/// Build a count for every day from `start` through `end`, inclusive,
+/// taking values from `counts` and filling in zero for days without any.
+pub fn fill_daily_counts(
+    counts: &[(NaiveDate, i64)],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> HashMap<NaiveDate, u32> {
+    let mut days = HashMap::new();
+
+    let mut day = start;
+    while day <= end {
+        days.insert(day, 0);
+        day = day.succ();
+    }
+
+    for (day, count) in counts {
+        if let Some(total) = days.get_mut(day) {
+            *total = *count as u32;
+        }
+    }
+
+    days
+}
+
+/// Totals across all of a person's entries.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PersonStats {
+    pub total_entries: u32,
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+    pub first_entry: Option<NaiveDate>,
+    pub last_entry: Option<NaiveDate>,
+}
+
+impl PersonStats {
+    pub fn from_entries(entries: &[Entry], standard: &DrinkStandard) -> PersonStats {
+        let total = entries.aggregate_with_standard(standard);
+
+        PersonStats {
+            total_entries: entries.len() as u32,
+            min_drinks: total.min_drinks,
+            max_drinks: total.max_drinks,
+            first_entry: entries.iter().map(|entry| entry.drank_on).min(),
+            last_entry: entries.iter().map(|entry| entry.drank_on).max(),
+        }
+    }
+}
+
+/// Totals for the entries of a single week, starting on Monday.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WeeklyReport {
+    pub week_start: NaiveDate,
+    pub total_entries: u32,
+    pub drinking_days: u32,
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+}
+
+impl WeeklyReport {
+    /// Summarize the entries within the week which includes `day`.
+    pub fn from_entries(
+        entries: &[Entry],
+        day: NaiveDate,
+        standard: &DrinkStandard,
+    ) -> WeeklyReport {
+        let week_start = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+        let week_end = week_start + Duration::days(6);
+
+        let week_entries: Vec<Entry> = entries
+            .iter()
+            .filter(|entry| entry.drank_on >= week_start && entry.drank_on <= week_end)
+            .cloned()
+            .collect();
+
+        let drinking_days = week_entries
+            .iter()
+            .map(|entry| entry.drank_on)
+            .collect::<HashSet<NaiveDate>>()
+            .len();
+        let total = week_entries[..].aggregate_with_standard(standard);
+
+        WeeklyReport {
+            week_start,
+            total_entries: week_entries.len() as u32,
+            drinking_days: drinking_days as u32,
+            min_drinks: total.min_drinks,
+            max_drinks: total.max_drinks,
+        }
+    }
+}
+
+/// How many entries there are of a single drink.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DrinkFrequency {
+    pub drink_id: i32,
+    pub name: String,
+    pub count: u32,
+}
+
+/// Find the `limit` drinks with the most entries, from most to least common, and then by name.
+pub fn top_drinks(entries: &[Entry], limit: usize) -> Vec<DrinkFrequency> {
+    let mut drinks: HashMap<i32, DrinkFrequency> = HashMap::new();
+    for entry in entries {
+        drinks
+            .entry(entry.drink_id)
+            .or_insert_with(|| DrinkFrequency {
+                drink_id: entry.drink_id,
+                name: entry.name.clone(),
+                count: 0,
+            })
+            .count += 1;
+    }
+
+    let mut drinks: Vec<DrinkFrequency> = drinks.into_iter().map(|(_, drink)| drink).collect();
+    drinks.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    drinks.truncate(limit);
+    drinks
+}
+
+/// Totals for the entries of a single calendar month.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MonthSummary {
+    pub year: i32,
+    pub month: u32,
+    pub total_entries: u32,
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+
+    /// The number of distinct days with at least one entry.
+    pub drinking_days: u32,
+}
+
+/// Summarize each month of `year`; all twelve months are included, even those without entries.
+pub fn month_summaries(
+    entries: &[Entry],
+    year: i32,
+    standard: &DrinkStandard,
+) -> Vec<MonthSummary> {
+    (1..=12)
+        .map(|month| {
+            let month_entries: Vec<&Entry> = entries
+                .iter()
+                .filter(|entry| entry.drank_on.year() == year && entry.drank_on.month() == month)
+                .collect();
+
+            let (min_drinks, max_drinks) = month_entries
+                .iter()
+                .map(|entry| entry.aggregate_with_standard(standard))
+                .fold((0.0, 0.0), |(min, max), aggregate| {
+                    (min + aggregate.min_drinks, max + aggregate.max_drinks)
+                });
+
+            let drinking_days = month_entries
+                .iter()
+                .map(|entry| entry.drank_on)
+                .collect::<HashSet<NaiveDate>>()
+                .len();
+
+            MonthSummary {
+                year,
+                month,
+                total_entries: month_entries.len() as u32,
+                min_drinks,
+                max_drinks,
+                drinking_days: drinking_days as u32,
+            }
+        })
+        .collect()
+}
+
+/// Totals for the entries of a single year.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct YearlySummary {
+    pub year: i32,
+    pub total_entries: u32,
+    pub unique_drinks: u32,
+    pub drinking_days: u32,
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+
+    /// How many months of the year had no entries.
+    pub dry_months: u32,
+}
+
+/// Summarize each year in which any of `entries` were drunk, in ascending order.
+pub fn yearly_summaries(entries: &[Entry], standard: &DrinkStandard) -> Vec<YearlySummary> {
+    let mut years: BTreeMap<i32, Vec<&Entry>> = BTreeMap::new();
+    for entry in entries {
+        years.entry(entry.drank_on.year()).or_default().push(entry);
+    }
+
+    years
+        .into_iter()
+        .map(|(year, year_entries)| {
+            let months: Vec<MonthSummary> = month_summaries(entries, year, standard);
+
+            let unique_drinks = year_entries
+                .iter()
+                .map(|entry| entry.drink_id)
+                .collect::<HashSet<i32>>()
+                .len();
+
+            YearlySummary {
+                year,
+                total_entries: year_entries.len() as u32,
+                unique_drinks: unique_drinks as u32,
+                drinking_days: months.iter().map(|month| month.drinking_days).sum(),
+                min_drinks: months.iter().map(|month| month.min_drinks).sum(),
+                max_drinks: months.iter().map(|month| month.max_drinks).sum(),
+                dry_months: months
+                    .iter()
+                    .filter(|month| month.total_entries == 0)
+                    .count() as u32,
+            }
+        })
+        .collect()
+}
+
+/// Calculate the running total of `(min_drinks, max_drinks)` after each of `entries`.
+pub fn running_totals(entries: &[Entry], standard: &DrinkStandard) -> Vec<(f32, f32)> {
+    entries
+        .iter()
+        .scan((0.0, 0.0), |(min, max), entry| {
+            let aggregate = entry.aggregate_with_standard(standard);
+            *min += aggregate.min_drinks;
+            *max += aggregate.max_drinks;
+            Some((*min, *max))
+        })
+        .collect()
+}
+
+/// Every entry drunk on a single occasion, ex: the evening of a given day.
+pub struct DrinkingSession {
+    pub date: NaiveDate,
+    pub time_period: TimePeriod,
+
+    /// Every context of the session's entries, without duplicates.
+    pub context: Vec<String>,
+    pub entries: Vec<Entry>,
+    pub total: DrinkAggregate,
+}
+
+/// Group `entries` into sessions by the day and time period in which they were drank,
+/// most recent first.
+pub fn drinking_sessions(entries: Vec<Entry>, standard: &DrinkStandard) -> Vec<DrinkingSession> {
+    let mut occasions: BTreeMap<(NaiveDate, TimePeriod), Vec<Entry>> = BTreeMap::new();
+
+    for entry in entries {
+        occasions
+            .entry((entry.drank_on, entry.time))
+            .or_default()
+            .push(entry);
+    }
+
+    occasions
+        .into_iter()
+        .rev()
+        .map(|((date, time_period), entries)| {
+            let mut context: Vec<String> = Vec::new();
+            for tag in entries.iter().flat_map(|entry| entry.context.iter()) {
+                if !context.contains(tag) {
+                    context.push(tag.clone());
+                }
+            }
+
+            DrinkingSession {
+                date,
+                time_period,
+                context,
+                total: entries.aggregate_with_standard(standard),
+                entries,
+            }
+        })
+        .collect()
+}
+
+/// An entry in a drink's history, along with the totals up to and including it.
+#[derive(Serialize)]
+pub struct DrinkHistoryEntry {
+    #[serde(flatten)]
+    pub entry: Entry,
+    pub cumulative_min_drinks: f32,
+    pub cumulative_max_drinks: f32,
+}
+
+/// Every entry of a single drink, in chronological order.
+#[derive(Serialize)]
+#[serde(rename = "history")]
+pub struct DrinkHistory {
+    pub drink_id: i32,
+    pub drink_name: String,
+    pub total_occurrences: u32,
+    pub first_seen: NaiveDate,
+    pub last_seen: NaiveDate,
+    pub total_min_drinks: f32,
+    pub total_max_drinks: f32,
+    pub entries: Vec<DrinkHistoryEntry>,
+}
+
+impl DrinkHistory {
+    /// Build the history of `entries`, which should all be of the same drink.
+    /// Returns `None` if there are no entries.
+    pub fn from_entries(mut entries: Vec<Entry>, standard: &DrinkStandard) -> Option<DrinkHistory> {
+        entries.sort_by_key(|entry| (entry.drank_on, entry.time));
+
+        let first = entries.first()?;
+        let last = entries.last()?;
+        let (drink_id, drink_name) = (first.drink_id, first.name.clone());
+        let (first_seen, last_seen) = (first.drank_on, last.drank_on);
+
+        let totals = running_totals(&entries, standard);
+        let (total_min_drinks, total_max_drinks) = *totals.last()?;
+
+        Some(DrinkHistory {
+            drink_id,
+            drink_name,
+            total_occurrences: entries.len() as u32,
+            first_seen,
+            last_seen,
+            total_min_drinks,
+            total_max_drinks,
+            entries: entries
+                .into_iter()
+                .zip(totals)
+                .map(|(entry, (min, max))| DrinkHistoryEntry {
+                    entry,
+                    cumulative_min_drinks: min,
+                    cumulative_max_drinks: max,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// The entries of a single month within a context timeline.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ContextTimelinePoint {
+    pub year: i32,
+    pub month: u32,
+    pub entry_count: u32,
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+}
+
+/// Group `entries` by month, in chronological order. Months without entries are omitted.
+pub fn context_timeline(entries: &[Entry], standard: &DrinkStandard) -> Vec<ContextTimelinePoint> {
+    let mut months: BTreeMap<(i32, u32), ContextTimelinePoint> = BTreeMap::new();
+
+    for entry in entries {
+        let (year, month) = (entry.drank_on.year(), entry.drank_on.month());
+        let aggregate = entry.aggregate_with_standard(standard);
+
+        let point = months.entry((year, month)).or_insert(ContextTimelinePoint {
+            year,
+            month,
+            entry_count: 0,
+            min_drinks: 0.0,
+            max_drinks: 0.0,
+        });
+        point.entry_count += 1;
+        point.min_drinks += aggregate.min_drinks;
+        point.max_drinks += aggregate.max_drinks;
+    }
+
+    months.into_iter().map(|(_, point)| point).collect()
+}
+
+/// Find the `percentile` (0 to 100) of the expected number of drinks per day,
+/// over the days on which `entries` were drunk, interpolating between days as needed.
+///
+/// Returns zero if there are no entries.
+pub fn percentile_daily_drinks(entries: &[Entry], percentile: u8, standard: &DrinkStandard) -> f32 {
+    let mut days: BTreeMap<NaiveDate, f32> = BTreeMap::new();
+    for entry in entries {
+        *days.entry(entry.drank_on).or_insert(0.0) +=
+            entry.aggregate_with_standard(standard).expected_drinks();
+    }
+
+    let mut daily: Vec<f32> = days.into_iter().map(|(_, drinks)| drinks).collect();
+    daily.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let (first, last) = match (daily.first(), daily.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return 0.0,
+    };
+
+    match percentile.min(100) {
+        0 => first,
+        100 => last,
+        percentile => {
+            let rank = (percentile as f32 / 100.0) * (daily.len() - 1) as f32;
+            let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+            daily[lower] + (daily[upper] - daily[lower]) * (rank - lower as f32)
+        }
+    }
+}
+
+/// The entries drunk on a single day of a calendar.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub entry_count: u32,
+    pub min_drinks: f32,
+    pub max_drinks: f32,
+}
+
+/// A month laid out as calendar weeks, from Monday to Sunday.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename = "calendar")]
+pub struct CalendarMatrix {
+    pub year: i32,
+    pub month: u32,
+
+    /// Each week has seven cells; days outside of the month are `None`.
+    pub weeks: Vec<Vec<Option<CalendarDay>>>,
+}
+
+impl CalendarMatrix {
+    /// Lay out the days of the given month, along with the `entries` drunk on each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `month` is not between 1 and 12.
+    pub fn for_month(
+        year: i32,
+        month: u32,
+        entries: &[Entry],
+        standard: &DrinkStandard,
+    ) -> CalendarMatrix {
+        let first = NaiveDate::from_ymd(year, month, 1);
+        let next_month = match month {
+            12 => NaiveDate::from_ymd(year + 1, 1, 1),
+            _ => NaiveDate::from_ymd(year, month + 1, 1),
+        };
+
+        let mut days: BTreeMap<NaiveDate, CalendarDay> = BTreeMap::new();
+        let mut date = first;
+        while date < next_month {
+            let day = CalendarDay {
+                date,
+                entry_count: 0,
+                min_drinks: 0.0,
+                max_drinks: 0.0,
+            };
+            days.insert(date, day);
+            date = date.succ();
+        }
+
+        for entry in entries {
+            if let Some(day) = days.get_mut(&entry.drank_on) {
+                let aggregate = entry.aggregate_with_standard(standard);
+                day.entry_count += 1;
+                day.min_drinks += aggregate.min_drinks;
+                day.max_drinks += aggregate.max_drinks;
+            }
+        }
+
+        let padding = first.weekday().num_days_from_monday() as usize;
+        let mut cells: Vec<Option<CalendarDay>> = (0..padding).map(|_| None).collect();
+        cells.extend(days.into_iter().map(|(_, day)| Some(day)));
+        while cells.len() % 7 != 0 {
+            cells.push(None);
+        }
+
+        let mut weeks = Vec::new();
+        while !cells.is_empty() {
+            let rest = cells.split_off(7);
+            weeks.push(cells);
+            cells = rest;
+        }
+
+        CalendarMatrix { year, month, weeks }
+    }
+}
+
+/// A calendar month in which no entries were recorded.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DryMonth {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl From<(i32, u32)> for DryMonth {
+    fn from((year, month): (i32, u32)) -> DryMonth {
+        DryMonth { year, month }
+    }
+}
+
+/// Find every `(year, month)` from January of `start_year` through December
+/// of `end_year` (inclusive) in which none of `entries` were drunk.
+pub fn dry_months(entries: &[Entry], start_year: i32, end_year: i32) -> Vec<(i32, u32)> {
+    let wet_months: HashSet<(i32, u32)> = entries
+        .iter()
+        .map(|entry| (entry.drank_on.year(), entry.drank_on.month()))
+        .collect();
+
+    (start_year..=end_year)
+        .flat_map(|year| (1..=12).map(move |month| (year, month)))
+        .filter(|year_month| !wet_months.contains(year_month))
+        .collect()
+}
+
+/// Count how many `entries` were drunk during each time period,
+/// with all four periods listed in chronological order.
+pub fn time_period_distribution(entries: &[Entry]) -> Vec<(TimePeriod, u32)> {
+    let periods = [
+        TimePeriod::Morning,
+        TimePeriod::Afternoon,
+        TimePeriod::Evening,
+        TimePeriod::Night,
+    ];
+
+    periods
+        .iter()
+        .map(|period| {
+            let count = entries.iter().filter(|entry| entry.time == *period).count();
+            (*period, count as u32)
+        })
+        .collect()
+}
+
+/// Find the time period during which the most `entries` were drunk.
+/// Ties go to whichever period is earliest in the day.
+pub fn most_common_time_period(entries: &[Entry]) -> Option<TimePeriod> {
+    if entries.is_empty() {
+        return None;
+    }
+
+    time_period_distribution(entries)
+        .into_iter()
+        .fold(None, |most_common, (period, count)| match most_common {
+            Some((_, max_count)) if max_count >= count => most_common,
+            _ => Some((period, count)),
+        })
+        .map(|(period, _)| period)
+}
+
+/// The average number of drinks per drinking day, on weekdays compared to weekends.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename = "weekend_vs_weekday")]
+pub struct WeekdayWeekendReport {
+    pub weekday_avg_drinks: f32,
+    pub weekend_avg_drinks: f32,
+
+    /// How many distinct weekdays (Monday through Friday) had entries.
+    pub weekday_days_count: u32,
+
+    /// How many distinct weekend days (Saturday and Sunday) had entries.
+    pub weekend_days_count: u32,
+}
+
+/// Compare the expected number of drinks per drinking day on weekdays and weekends.
+/// Averages are zero for a group without any drinking days.
+pub fn weekend_vs_weekday(entries: &[Entry], standard: &DrinkStandard) -> WeekdayWeekendReport {
+    let mut days: BTreeMap<NaiveDate, f32> = BTreeMap::new();
+    for entry in entries {
+        *days.entry(entry.drank_on).or_insert(0.0) +=
+            entry.aggregate_with_standard(standard).expected_drinks();
+    }
+
+    let (weekend, weekday): (Vec<(NaiveDate, f32)>, Vec<(NaiveDate, f32)>) = days
+        .into_iter()
+        .partition(|(day, _)| day.weekday().num_days_from_monday() >= 5);
+
+    let average = |days: &[(NaiveDate, f32)]| match days.len() {
+        0 => 0.0,
+        count => days.iter().map(|(_, drinks)| drinks).sum::<f32>() / count as f32,
+    };
+
+    WeekdayWeekendReport {
+        weekday_avg_drinks: average(&weekday),
+        weekend_avg_drinks: average(&weekend),
+        weekday_days_count: weekday.len() as u32,
+        weekend_days_count: weekend.len() as u32,
+    }
+}
+
+/// How many entries were tagged with `context` on a given day of the week.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ContextHeatmapCell {
+    /// Days since Monday, so Monday is 0 and Sunday is 6.
+    pub weekday: u8,
+    pub context: String,
+    pub count: u32,
+}
+
+/// Count the entries for every combination of weekday and context tag,
+/// ordered by weekday and then context. Combinations with no entries are omitted.
+pub fn context_heatmap(entries: &[Entry]) -> Vec<ContextHeatmapCell> {
+    let mut counts: BTreeMap<(u8, &str), u32> = BTreeMap::new();
+
+    for entry in entries {
+        let weekday = entry.drank_on.weekday().num_days_from_monday() as u8;
+        for context in entry.context.iter() {
+            *counts.entry((weekday, context.as_str())).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|((weekday, context), count)| ContextHeatmapCell {
+            weekday,
+            context: context.to_string(),
+            count,
+        })
+        .collect()
+}
+
+/// Count how many entries were tagged with each context.
+pub fn context_breakdown(entries: &[Entry]) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+
+    for context in entries.iter().flat_map(|entry| entry.context.iter()) {
+        *counts.entry(context.clone()).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// How many entries were tagged with `context`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ContextCount {
+    pub context: String,
+    pub count: u32,
+}
+
+/// Count the entries of each context which was used at least `min_count` times,
+/// ordered from most to least common, and then by context.
+pub fn context_counts(entries: &[Entry], min_count: u32) -> Vec<ContextCount> {
+    let mut counts: Vec<ContextCount> = context_breakdown(entries)
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|(context, count)| ContextCount { context, count })
+        .collect();
+
+    counts.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.context.cmp(&b.context))
+    });
+    counts
+}
+
+/// The total volume of the entries recorded in a single unit.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct VolumeBreakdown {
+    /// The unit in which the entries' volumes were recorded,
+    /// or `None` for entries without any volume.
+    pub unit: Option<VolumeUnit>,
+    pub total_amount_ml: f32,
+    pub entry_count: u32,
+}
+
+/// Group entries by the unit of their volume, summing the volume of a serving
+/// of each entry in mL, ordered from the largest total to the smallest.
+pub fn volume_breakdown(entries: &[Entry]) -> Vec<VolumeBreakdown> {
+    let mut units: HashMap<Option<VolumeUnit>, (f32, u32)> = HashMap::new();
+
+    for entry in entries {
+        let volume_ml = entry.volume_ml.or(entry.volume.map(|v| v.to_ml()));
+        let (total, count) = units
+            .entry(entry.volume.map(|v| v.unit))
+            .or_insert((0.0, 0));
+
+        *total += volume_ml.map(|v| v.amount.num).unwrap_or(0.0);
+        *count += 1;
+    }
+
+    let mut breakdown: Vec<VolumeBreakdown> = units
+        .into_iter()
+        .map(|(unit, (total_amount_ml, entry_count))| VolumeBreakdown {
+            unit,
+            total_amount_ml,
+            entry_count,
+        })
+        .collect();
+
+    breakdown.sort_by(|a, b| {
+        b.total_amount_ml
+            .partial_cmp(&a.total_amount_ml)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    breakdown
+}
+
+/// A summary of the ABV of the drinks in a set of entries.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename = "abv_profile")]
+pub struct AbvProfile {
+    pub mean_min_abv: f32,
+    pub mean_max_abv: f32,
+
+    /// The population standard deviation of the minimum ABV.
+    pub std_dev_abv: f32,
+
+    /// How many entries were excluded for lack of ABV information.
+    pub entries_without_abv: u32,
+
+    /// How many entries were included in the profile.
+    pub entries_with_abv: u32,
+}
+
+impl AbvProfile {
+    /// Whether enough entries have ABV information (fewer than 20% lacking it)
+    /// for the profile to be representative.
+    pub fn is_reliable(&self) -> bool {
+        let total = self.entries_with_abv + self.entries_without_abv;
+        total > 0 && (self.entries_without_abv as f32) < (total as f32 * 0.2)
+    }
+}
+
+/// Summarize the ABV of every entry that has ABV information.
+///
+/// The means and standard deviation are zero if no entries have ABV information.
+pub fn abv_profile(entries: &[Entry]) -> AbvProfile {
+    let abvs: Vec<(f32, f32)> = entries
+        .iter()
+        .filter_map(|entry| entry.abv())
+        .map(|abv| (abv.min.num, abv.max.num))
+        .collect();
+
+    let count = abvs.len() as u32;
+    let entries_without_abv = entries.len() as u32 - count;
+
+    if count == 0 {
+        return AbvProfile {
+            mean_min_abv: 0.0,
+            mean_max_abv: 0.0,
+            std_dev_abv: 0.0,
+            entries_without_abv,
+            entries_with_abv: 0,
+        };
+    }
+
+    let (sum_min, sum_max, sum_squares) = abvs.iter().fold(
+        (0.0, 0.0, 0.0),
+        |(sum_min, sum_max, sum_squares), (min, max)| {
+            (sum_min + min, sum_max + max, sum_squares + min * min)
+        },
+    );
+
+    let mean_min_abv = sum_min / count as f32;
+    let variance = (sum_squares / count as f32 - mean_min_abv * mean_min_abv).max(0.0);
+
+    AbvProfile {
+        mean_min_abv,
+        mean_max_abv: sum_max / count as f32,
+        std_dev_abv: variance.sqrt(),
+        entries_without_abv,
+        entries_with_abv: count,
+    }
+}
+
+/// The average ABV of the entries drunk within a single month or week.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AbvTimelinePoint {
+    /// The period, ex: "2020-05" for a month, or "2020-W19" for an ISO week.
+    pub label: String,
+    pub avg_min_abv: f32,
+    pub avg_max_abv: f32,
+    pub entry_count: u32,
+}
+
+/// Group the entries that have ABV information by month, or by ISO week if `granularity`
+/// is "week", and average their ABV, in chronological order.
+///
+/// Entries without ABV information are ignored, so periods without any ABV are omitted.
+pub fn abv_timeline(entries: &[Entry], granularity: &str) -> Vec<AbvTimelinePoint> {
+    let mut periods: BTreeMap<String, (f32, f32, u32)> = BTreeMap::new();
+
+    for entry in entries {
+        let (min_abv, max_abv) = match (entry.min_abv(), entry.max_abv()) {
+            (Some(min_abv), Some(max_abv)) => (min_abv, max_abv),
+            _ => continue,
+        };
+
+        let label = if granularity == "week" {
+            let week = entry.drank_on.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        } else {
+            format!("{}-{:02}", entry.drank_on.year(), entry.drank_on.month())
+        };
+
+        let (sum_min, sum_max, count) = periods.entry(label).or_insert((0.0, 0.0, 0));
+        *sum_min += min_abv;
+        *sum_max += max_abv;
+        *count += 1;
+    }
+
+    periods
+        .into_iter()
+        .map(|(label, (sum_min, sum_max, count))| AbvTimelinePoint {
+            label,
+            avg_min_abv: sum_min / count as f32,
+            avg_max_abv: sum_max / count as f32,
+            entry_count: count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ApproxF32, VolumeUnit};
+    use chrono::Utc;
+
+    /// Make an entry without any ABV or volume information.
+    fn make_entry(drank_on: NaiveDate, time: TimePeriod, quantity: f32) -> Entry {
+        Entry {
+            id: 1,
+            drank_on,
+            time,
+            context: Vec::new(),
+            drink_id: 1,
+            name: "beer".into(),
+            min_abv: None,
+            max_abv: None,
+            multiplier: 1.0,
+            min_quantity: ApproxF32::new(quantity, false),
+            max_quantity: ApproxF32::new(quantity, false),
+            volume: None,
+            volume_ml: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_yearly_summaries() {
+        let entry = |year, month, day, drink_id, quantity| {
+            let mut entry = make_entry(
+                NaiveDate::from_ymd(year, month, day),
+                TimePeriod::Evening,
+                quantity,
+            );
+            entry.drink_id = drink_id;
+            entry
+        };
+        let entries = vec![
+            entry(2019, 12, 31, 1, 2.0),
+            entry(2020, 1, 1, 1, 1.0),
+            entry(2020, 1, 1, 2, 1.0),
+            entry(2020, 3, 15, 3, 2.0),
+            entry(2018, 6, 1, 1, 3.0),
+        ];
+
+        let summaries = yearly_summaries(&entries, &DrinkStandard::default());
+
+        assert_eq!(
+            vec![2018, 2019, 2020],
+            summaries.iter().map(|s| s.year).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            YearlySummary {
+                year: 2020,
+                total_entries: 3,
+                unique_drinks: 3,
+                drinking_days: 2,
+                min_drinks: 4.0,
+                max_drinks: 4.0,
+                dry_months: 10,
+            },
+            summaries[2]
+        );
+        assert_eq!(1, summaries[1].total_entries);
+        assert_eq!(11, summaries[1].dry_months);
+        assert_eq!(3.0, summaries[0].max_drinks);
+        assert!(yearly_summaries(&[], &DrinkStandard::default()).is_empty());
+    }
+
+    #[test]
+    fn test_volume_breakdown() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entry = |amount, unit| {
+            let mut entry = make_entry(date, TimePeriod::Evening, 1.0);
+            let volume = LiquidVolume {
+                amount: ApproxF32::new(amount, false),
+                unit,
+            };
+            entry.volume = Some(volume);
+            entry.volume_ml = Some(volume.to_ml());
+            entry
+        };
+        let entries = vec![
+            entry(330.0, VolumeUnit::mL),
+            entry(1.5, VolumeUnit::FlOz),
+            entry(500.0, VolumeUnit::mL),
+            entry(1.5, VolumeUnit::FlOz),
+            entry(1.0, VolumeUnit::UkPint),
+            make_entry(date, TimePeriod::Evening, 1.0),
+        ];
+
+        let breakdown = volume_breakdown(&entries);
+
+        assert_eq!(4, breakdown.len());
+        assert_eq!(Some(VolumeUnit::mL), breakdown[0].unit);
+        assert_eq!(830.0, breakdown[0].total_amount_ml);
+        assert_eq!(2, breakdown[0].entry_count);
+        assert_eq!(Some(VolumeUnit::UkPint), breakdown[1].unit);
+        assert!((breakdown[1].total_amount_ml - 568.261).abs() < 0.01);
+        assert_eq!(Some(VolumeUnit::FlOz), breakdown[2].unit);
+        assert!((breakdown[2].total_amount_ml - 88.72).abs() < 0.01);
+        assert_eq!(2, breakdown[2].entry_count);
+        assert_eq!(
+            VolumeBreakdown {
+                unit: None,
+                total_amount_ml: 0.0,
+                entry_count: 1,
+            },
+            breakdown[3]
+        );
+
+        assert!(volume_breakdown(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_weekend_vs_weekday() {
+        // 4 May 2020 was a Monday.
+        let day = |day| NaiveDate::from_ymd(2020, 5, day);
+        let entries = vec![
+            make_entry(day(4), TimePeriod::Evening, 2.0),
+            make_entry(day(5), TimePeriod::Evening, 1.0),
+            make_entry(day(5), TimePeriod::Night, 3.0),
+            make_entry(day(8), TimePeriod::Evening, 3.0),
+            make_entry(day(9), TimePeriod::Evening, 5.0),
+            make_entry(day(10), TimePeriod::Afternoon, 3.0),
+        ];
+
+        assert_eq!(
+            WeekdayWeekendReport {
+                weekday_avg_drinks: 3.0,
+                weekend_avg_drinks: 4.0,
+                weekday_days_count: 3,
+                weekend_days_count: 2,
+            },
+            weekend_vs_weekday(&entries, &DrinkStandard::default())
+        );
+    }
+
+    #[test]
+    fn test_weekend_vs_weekday_without_weekends() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Evening, 2.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 6), TimePeriod::Evening, 1.0),
+        ];
+
+        let report = weekend_vs_weekday(&entries, &DrinkStandard::default());
+        assert_eq!(1.5, report.weekday_avg_drinks);
+        assert_eq!(0.0, report.weekend_avg_drinks);
+        assert_eq!(0, report.weekend_days_count);
+
+        let empty = weekend_vs_weekday(&[], &DrinkStandard::default());
+        assert_eq!(0.0, empty.weekday_avg_drinks);
+        assert_eq!(0.0, empty.weekend_avg_drinks);
+    }
+
+    #[test]
+    fn test_drink_aggregate_sum() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let mut with_volume = make_entry(date, TimePeriod::Evening, 2.0);
+        with_volume.volume = Some(LiquidVolume {
+            amount: ApproxF32::new(330.0, false),
+            unit: VolumeUnit::mL,
+        });
+        let entries = vec![
+            make_entry(date, TimePeriod::Evening, 1.0),
+            with_volume,
+            make_entry(date, TimePeriod::Night, 3.0),
+        ];
+
+        let total = entries[..].aggregate();
+        assert_eq!(6.0, total.min_drinks);
+        assert_eq!(6.0, total.max_drinks);
+        assert_eq!(660.0, total.min_volume.unwrap().amount.num);
+        assert_eq!(660.0, total.max_volume.unwrap().amount.num);
+
+        let zero = Vec::<Entry>::new()[..].aggregate();
+        assert_eq!(0.0, zero.min_drinks);
+        assert_eq!(0.0, zero.max_drinks);
+        assert!(zero.min_volume.is_none());
+        assert!(zero.max_volume.is_none());
+    }
+
+    #[test]
+    fn test_aggregate_day() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let mut pint = make_entry(date, TimePeriod::Evening, 1.0);
+        pint.volume = Some(LiquidVolume {
+            amount: ApproxF32::new(1.0, false),
+            unit: VolumeUnit::UkPint,
+        });
+        let mut can = make_entry(date, TimePeriod::Night, 2.0);
+        can.volume = Some(LiquidVolume {
+            amount: ApproxF32::new(330.0, true),
+            unit: VolumeUnit::mL,
+        });
+
+        let day = aggregate_day(
+            &[pint, can, make_entry(date, TimePeriod::Night, 1.5)],
+            &DrinkStandard::default(),
+        );
+        assert_eq!(4.5, day.min_drinks);
+        assert_eq!(4.5, day.max_drinks);
+
+        // Volumes in different units are summed in the unit of the first.
+        let min_volume = day.min_volume.unwrap();
+        assert_eq!(VolumeUnit::UkPint, min_volume.unit);
+        assert!(min_volume.amount.is_approximate);
+        assert!((min_volume.to_ml().amount.num - 1228.261).abs() < 0.1);
+
+        assert_eq!(
+            DailyAggregate {
+                min_drinks: 0.0,
+                max_drinks: 0.0,
+                min_volume: None,
+                max_volume: None,
+            },
+            aggregate_day(&[], &DrinkStandard::default())
+        );
+    }
+
+    #[test]
+    fn test_budget_on_track() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![
+            make_entry(date, TimePeriod::Evening, 2.0),
+            make_entry(date, TimePeriod::Night, 3.0),
+        ];
+
+        let budget = DrinkBudget::from_entries(&entries, 14.0, &DrinkStandard::UK);
+
+        assert_eq!(5.0, budget.this_week_used_max);
+        assert_eq!(9.0, budget.remaining_min);
+        assert!(budget.on_track);
+    }
+
+    #[test]
+    fn test_budget_at_limit() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![make_entry(date, TimePeriod::Evening, 14.0)];
+
+        let budget = DrinkBudget::from_entries(&entries, 14.0, &DrinkStandard::UK);
+
+        assert_eq!(0.0, budget.remaining_min);
+        assert!(budget.on_track);
+    }
+
+    #[test]
+    fn test_budget_over_limit() {
+        let budget = DrinkBudget::new(14.0, 12.0, 16.5);
+
+        assert_eq!(-2.5, budget.remaining_min);
+        assert_eq!(2.0, budget.remaining_max);
+        assert!(!budget.on_track);
+    }
+
+    #[test]
+    fn test_streak_history() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 5, 1), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 2), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 2), TimePeriod::Night, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 3), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 7), TimePeriod::Evening, 1.0),
+        ];
+
+        let history = streak_history(&entries, NaiveDate::from_ymd(2020, 5, 10));
+
+        let expected = vec![
+            (StreakKind::Drinking, (2020, 5, 1), (2020, 5, 3), 3),
+            (StreakKind::Sober, (2020, 5, 4), (2020, 5, 6), 3),
+            (StreakKind::Drinking, (2020, 5, 7), (2020, 5, 7), 1),
+            (StreakKind::Sober, (2020, 5, 8), (2020, 5, 10), 3),
+        ];
+        let expected: Vec<StreakPeriod> = expected
+            .into_iter()
+            .map(|(kind, start, end, days)| StreakPeriod {
+                kind,
+                start: NaiveDate::from_ymd(start.0, start.1, start.2),
+                end: NaiveDate::from_ymd(end.0, end.1, end.2),
+                days,
+            })
+            .collect();
+
+        assert_eq!(expected, history);
+    }
+
+    #[test]
+    fn test_streak_history_ends_today() {
+        let today = NaiveDate::from_ymd(2020, 5, 10);
+        let entries = vec![make_entry(today, TimePeriod::Evening, 1.0)];
+
+        let history = streak_history(&entries, today);
+
+        assert_eq!(
+            vec![StreakPeriod {
+                kind: StreakKind::Drinking,
+                start: today,
+                end: today,
+                days: 1,
+            }],
+            history
+        );
+        assert!(streak_history(&[], today).is_empty());
+    }
+
+    #[test]
+    fn test_fill_daily_counts() {
+        let start = NaiveDate::from_ymd(2020, 2, 27);
+        let end = NaiveDate::from_ymd(2020, 3, 2);
+        let counts = vec![
+            (NaiveDate::from_ymd(2020, 2, 28), 2),
+            (NaiveDate::from_ymd(2020, 3, 1), 1),
+            // Outside of the range, so ignored.
+            (NaiveDate::from_ymd(2020, 3, 3), 4),
+        ];
+
+        let days = fill_daily_counts(&counts, start, end);
+
+        assert_eq!(5, days.len());
+        assert_eq!(Some(&0), days.get(&NaiveDate::from_ymd(2020, 2, 27)));
+        assert_eq!(Some(&2), days.get(&NaiveDate::from_ymd(2020, 2, 28)));
+        assert_eq!(Some(&0), days.get(&NaiveDate::from_ymd(2020, 2, 29)));
+        assert_eq!(Some(&1), days.get(&NaiveDate::from_ymd(2020, 3, 1)));
+        assert_eq!(Some(&0), days.get(&NaiveDate::from_ymd(2020, 3, 2)));
+        assert_eq!(None, days.get(&NaiveDate::from_ymd(2020, 3, 3)));
+
+        let single = fill_daily_counts(&[], start, start);
+        assert_eq!(1, single.len());
+        assert_eq!(Some(&0), single.get(&start));
+    }
+
+    #[test]
+    fn test_streak_info() {
+        let day = |day| NaiveDate::from_ymd(2020, 5, day);
+        let entries = vec![
+            make_entry(day(1), TimePeriod::Evening, 1.0),
+            make_entry(day(2), TimePeriod::Evening, 1.0),
+            make_entry(day(6), TimePeriod::Evening, 1.0),
+            make_entry(day(8), TimePeriod::Evening, 1.0),
+            make_entry(day(9), TimePeriod::Evening, 1.0),
+        ];
+
+        let info = streak_info(&entries, day(9));
+
+        let current = info.current.unwrap();
+        assert_eq!(
+            (StreakKind::Drinking, day(8), 2),
+            (current.kind, current.start, current.days)
+        );
+        let drinking = info.longest_drinking.unwrap();
+        assert_eq!((day(1), 2), (drinking.start, drinking.days));
+        let sober = info.longest_sober.unwrap();
+        assert_eq!((day(3), day(5), 3), (sober.start, sober.end, sober.days));
+
+        let empty = streak_info(&[], day(9));
+        assert_eq!(None, empty.current);
+        assert_eq!(None, empty.longest_drinking);
+        assert_eq!(None, empty.longest_sober);
+    }
+
+    #[test]
+    fn test_person_stats() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 5, 9), TimePeriod::Evening, 2.0),
+            make_entry(NaiveDate::from_ymd(2019, 1, 1), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 1), TimePeriod::Night, 3.0),
+        ];
+
+        assert_eq!(
+            PersonStats {
+                total_entries: 3,
+                min_drinks: 6.0,
+                max_drinks: 6.0,
+                first_entry: Some(NaiveDate::from_ymd(2019, 1, 1)),
+                last_entry: Some(NaiveDate::from_ymd(2020, 5, 9)),
+            },
+            PersonStats::from_entries(&entries, &DrinkStandard::default())
+        );
+        assert_eq!(
+            None,
+            PersonStats::from_entries(&[], &DrinkStandard::default()).first_entry
+        );
+    }
+
+    #[test]
+    fn test_weekly_report() {
+        // 4 May 2020 was a Monday.
+        let day = |day| NaiveDate::from_ymd(2020, 5, day);
+        let entries = vec![
+            make_entry(day(3), TimePeriod::Evening, 4.0),
+            make_entry(day(4), TimePeriod::Evening, 1.0),
+            make_entry(day(4), TimePeriod::Night, 1.0),
+            make_entry(day(10), TimePeriod::Evening, 2.0),
+            make_entry(day(11), TimePeriod::Evening, 8.0),
+        ];
+
+        assert_eq!(
+            WeeklyReport {
+                week_start: day(4),
+                total_entries: 3,
+                drinking_days: 2,
+                min_drinks: 4.0,
+                max_drinks: 4.0,
+            },
+            WeeklyReport::from_entries(&entries, day(7), &DrinkStandard::default())
+        );
+    }
+
+    #[test]
+    fn test_top_drinks() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let drink = |drink_id, name: &str| {
+            let mut entry = make_entry(date, TimePeriod::Evening, 1.0);
+            entry.drink_id = drink_id;
+            entry.name = name.into();
+            entry
+        };
+        let entries = vec![
+            drink(1, "stout"),
+            drink(2, "lager"),
+            drink(3, "cider"),
+            drink(2, "lager"),
+            drink(1, "stout"),
+            drink(2, "lager"),
+        ];
+
+        let top = top_drinks(&entries, 2);
+
+        assert_eq!(
+            vec![(2, "lager", 3), (1, "stout", 2)],
+            top.iter()
+                .map(|d| (d.drink_id, d.name.as_str(), d.count))
+                .collect::<Vec<_>>()
+        );
+        assert!(top_drinks(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_entry_gaps() {
+        let days = vec![
+            NaiveDate::from_ymd(2019, 12, 20),
+            NaiveDate::from_ymd(2019, 12, 24),
+            NaiveDate::from_ymd(2020, 1, 3),
+            NaiveDate::from_ymd(2020, 1, 4),
+            NaiveDate::from_ymd(2020, 1, 11),
+            NaiveDate::from_ymd(2020, 1, 19),
+        ];
+
+        assert_eq!(
+            vec![
+                EntryGap {
+                    gap_start: NaiveDate::from_ymd(2019, 12, 24),
+                    gap_end: NaiveDate::from_ymd(2020, 1, 3),
+                    gap_days: 10,
+                },
+                EntryGap {
+                    gap_start: NaiveDate::from_ymd(2020, 1, 11),
+                    gap_end: NaiveDate::from_ymd(2020, 1, 19),
+                    gap_days: 8,
+                },
+            ],
+            entry_gaps(&days, 7)
+        );
+
+        assert_eq!(4, entry_gaps(&days, 1).len());
+        assert!(entry_gaps(&days, 10).is_empty());
+        assert!(entry_gaps(&days[..1], 1).is_empty());
+        assert!(entry_gaps(&[], 1).is_empty());
+    }
+
+    #[test]
+    fn test_dry_months() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2019, 12, 31), TimePeriod::Night, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 1), TimePeriod::Morning, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 31), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 3, 14), TimePeriod::Evening, 2.0),
+            make_entry(NaiveDate::from_ymd(2020, 12, 25), TimePeriod::Evening, 3.0),
+        ];
+
+        assert_eq!(
+            vec![
+                (2020, 2),
+                (2020, 4),
+                (2020, 5),
+                (2020, 6),
+                (2020, 7),
+                (2020, 8),
+                (2020, 9),
+                (2020, 10),
+                (2020, 11),
+            ],
+            dry_months(&entries, 2020, 2020)
+        );
+
+        assert_eq!(20, dry_months(&entries, 2019, 2020).len());
+        assert_eq!(12, dry_months(&entries, 2021, 2021).len());
+        assert_eq!(12, dry_months(&[], 2020, 2020).len());
+    }
+
+    #[test]
+    fn test_no_dry_months() {
+        let entries: Vec<Entry> = (1..=12)
+            .map(|month| {
+                let drank_on = NaiveDate::from_ymd(2020, month, 15);
+                make_entry(drank_on, TimePeriod::Evening, 1.0)
+            })
+            .collect();
+
+        assert!(dry_months(&entries, 2020, 2020).is_empty());
+    }
+
+    #[test]
+    fn test_time_period_distribution() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![
+            make_entry(date, TimePeriod::Night, 1.0),
+            make_entry(date, TimePeriod::Evening, 1.0),
+            make_entry(date, TimePeriod::Night, 2.0),
+            make_entry(date, TimePeriod::Afternoon, 1.0),
+            make_entry(date, TimePeriod::Night, 1.0),
+        ];
+
+        assert_eq!(
+            vec![
+                (TimePeriod::Morning, 0),
+                (TimePeriod::Afternoon, 1),
+                (TimePeriod::Evening, 1),
+                (TimePeriod::Night, 3),
+            ],
+            time_period_distribution(&entries)
+        );
+        assert_eq!(Some(TimePeriod::Night), most_common_time_period(&entries));
+    }
+
+    #[test]
+    fn test_most_common_time_period() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![
+            make_entry(date, TimePeriod::Evening, 1.0),
+            make_entry(date, TimePeriod::Morning, 1.0),
+            make_entry(date, TimePeriod::Evening, 1.0),
+        ];
+        assert_eq!(Some(TimePeriod::Evening), most_common_time_period(&entries));
+
+        // Ties go to the earliest time period.
+        let entries = vec![
+            make_entry(date, TimePeriod::Night, 1.0),
+            make_entry(date, TimePeriod::Afternoon, 1.0),
+        ];
+        assert_eq!(
+            Some(TimePeriod::Afternoon),
+            most_common_time_period(&entries)
+        );
+
+        assert_eq!(None, most_common_time_period(&[]));
+    }
+
+    #[test]
+    fn test_aggregate_with_abv() {
+        let mut entry = make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Evening, 2.0);
+        entry.min_abv = Some(ApproxF32::new(4.0, false));
+        entry.max_abv = Some(ApproxF32::new(6.0, false));
+        entry.volume = Some(LiquidVolume {
+            amount: ApproxF32::new(500.0, false),
+            unit: VolumeUnit::mL,
+        });
+        entry.volume_ml = entry.volume.map(|volume| volume.to_ml());
+
+        let aggregate = entry.aggregate_with_standard(&DrinkStandard::UK);
+
+        assert!((aggregate.min_drinks - 4.0).abs() < 0.001);
+        assert!((aggregate.max_drinks - 6.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_context_heatmap() {
+        let make_context_entry = |drank_on: NaiveDate, context: &[&str]| {
+            let mut entry = make_entry(drank_on, TimePeriod::Evening, 1.0);
+            entry.context = context.iter().map(|c| c.to_string()).collect();
+            entry
+        };
+
+        // 4 May 2020 and 11 May 2020 are Mondays; 9 May 2020 is a Saturday.
+        let entries = vec![
+            make_context_entry(NaiveDate::from_ymd(2020, 5, 4), &["pub"]),
+            make_context_entry(NaiveDate::from_ymd(2020, 5, 11), &["pub", "quiz"]),
+            make_context_entry(NaiveDate::from_ymd(2020, 5, 9), &["birthday"]),
+            make_context_entry(NaiveDate::from_ymd(2020, 5, 9), &["birthday", "pub"]),
+            make_context_entry(NaiveDate::from_ymd(2020, 5, 10), &[]),
+        ];
+
+        let cell = |weekday, context: &str, count| ContextHeatmapCell {
+            weekday,
+            context: context.into(),
+            count,
+        };
+
+        assert_eq!(
+            vec![
+                cell(0, "pub", 2),
+                cell(0, "quiz", 1),
+                cell(5, "birthday", 2),
+                cell(5, "pub", 1),
+            ],
+            context_heatmap(&entries)
+        );
+        assert!(context_heatmap(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_weighted_units() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+
+        // A pint of 5% lager contains 28.4 mL of alcohol.
+        let mut pint = make_entry(date, TimePeriod::Evening, 1.0);
+        pint.min_abv = Some(ApproxF32::new(5.0, false));
+        pint.max_abv = Some(ApproxF32::new(5.0, false));
+        pint.volume = Some(LiquidVolume {
+            amount: ApproxF32::new(568.261, false),
+            unit: VolumeUnit::mL,
+        });
+        pint.volume_ml = pint.volume;
+
+        let entries = vec![pint.clone(), pint, make_entry(date, TimePeriod::Night, 3.0)];
+
+        let uk = WeightedUnits::from_entries(&entries, "uk").unwrap();
+        assert_eq!("uk", uk.system);
+        assert!((uk.total_units_min - 5.683).abs() < 0.001);
+        assert!((uk.total_units_max - 5.683).abs() < 0.001);
+        assert_eq!(2, uk.entries_with_data);
+        assert_eq!(1, uk.entries_without_data);
+
+        let us = WeightedUnits::from_entries(&entries, "US").unwrap();
+        assert_eq!("us", us.system);
+        assert!((us.total_units_min - 3.211).abs() < 0.001);
+        assert!((us.total_units_max - 3.211).abs() < 0.001);
+        assert_eq!(2, us.entries_with_data);
+        assert_eq!(1, us.entries_without_data);
+
+        assert!(WeightedUnits::from_entries(&entries, "metric").is_none());
+    }
+
+    #[test]
+    fn test_month_summaries() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 1, 31), TimePeriod::Evening, 2.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 31), TimePeriod::Night, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 3), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 6, 20), TimePeriod::Evening, 3.0),
+            make_entry(NaiveDate::from_ymd(2019, 6, 20), TimePeriod::Evening, 5.0),
+        ];
+
+        let summaries = month_summaries(&entries, 2020, &DrinkStandard::default());
+        assert_eq!(12, summaries.len());
+
+        assert_eq!(
+            MonthSummary {
+                year: 2020,
+                month: 1,
+                total_entries: 3,
+                min_drinks: 4.0,
+                max_drinks: 4.0,
+                drinking_days: 2,
+            },
+            summaries[0]
+        );
+        assert_eq!(
+            MonthSummary {
+                year: 2020,
+                month: 2,
+                total_entries: 0,
+                min_drinks: 0.0,
+                max_drinks: 0.0,
+                drinking_days: 0,
+            },
+            summaries[1]
+        );
+        assert_eq!(1, summaries[5].total_entries);
+        assert_eq!(3.0, summaries[5].max_drinks);
+        assert_eq!(12, summaries[11].month);
+    }
+
+    #[test]
+    fn test_running_totals() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![
+            make_entry(date, TimePeriod::Evening, 2.0),
+            make_entry(date, TimePeriod::Night, 1.0),
+            make_entry(date, TimePeriod::Night, 3.0),
+        ];
+
+        assert_eq!(
+            vec![(2.0, 2.0), (3.0, 3.0), (6.0, 6.0)],
+            running_totals(&entries, &DrinkStandard::default())
+        );
+        assert!(running_totals(&[], &DrinkStandard::default()).is_empty());
+    }
+
+    #[test]
+    fn test_drink_history() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 5, 10), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Night, 2.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Afternoon, 3.0),
+        ];
+
+        let history = DrinkHistory::from_entries(entries, &DrinkStandard::default()).unwrap();
+
+        assert_eq!(1, history.drink_id);
+        assert_eq!("beer", history.drink_name);
+        assert_eq!(3, history.total_occurrences);
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 4), history.first_seen);
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 10), history.last_seen);
+        assert_eq!(6.0, history.total_max_drinks);
+
+        let cumulative: Vec<(TimePeriod, f32)> = history
+            .entries
+            .iter()
+            .map(|e| (e.entry.time, e.cumulative_max_drinks))
+            .collect();
+        assert_eq!(
+            vec![
+                (TimePeriod::Afternoon, 3.0),
+                (TimePeriod::Night, 5.0),
+                (TimePeriod::Evening, 6.0),
+            ],
+            cumulative
+        );
+
+        assert!(DrinkHistory::from_entries(Vec::new(), &DrinkStandard::default()).is_none());
+    }
+
+    #[test]
+    fn test_context_timeline() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 3, 20), TimePeriod::Night, 4.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 10), TimePeriod::Evening, 2.0),
+            make_entry(NaiveDate::from_ymd(2020, 1, 24), TimePeriod::Night, 1.0),
+            make_entry(NaiveDate::from_ymd(2019, 12, 31), TimePeriod::Night, 3.0),
+        ];
+
+        let point = |year, month, entry_count, drinks| ContextTimelinePoint {
+            year,
+            month,
+            entry_count,
+            min_drinks: drinks,
+            max_drinks: drinks,
+        };
+
+        assert_eq!(
+            vec![
+                point(2019, 12, 1, 3.0),
+                point(2020, 1, 2, 3.0),
+                point(2020, 3, 1, 4.0),
+            ],
+            context_timeline(&entries, &DrinkStandard::default())
+        );
+        assert!(context_timeline(&[], &DrinkStandard::default()).is_empty());
+    }
+
+    #[test]
+    fn test_expected_drinks() {
+        let aggregate = DrinkAggregate {
+            min_drinks: 2.0,
+            max_drinks: 3.0,
+            min_volume: None,
+            max_volume: None,
+        };
+
+        assert_eq!(2.5, aggregate.expected_drinks());
+    }
+
+    #[test]
+    fn test_percentile_daily_drinks() {
+        // Eleven days, drinking 1 through 11 drinks, split over two entries on some days.
+        let mut entries: Vec<Entry> = (1..=11)
+            .map(|day| {
+                let drank_on = NaiveDate::from_ymd(2020, 5, 12 - day);
+                make_entry(drank_on, TimePeriod::Evening, day as f32)
+            })
+            .collect();
+        entries[10].min_quantity = ApproxF32::new(10.0, false);
+        entries[10].max_quantity = ApproxF32::new(10.0, false);
+        entries.push(make_entry(
+            NaiveDate::from_ymd(2020, 5, 1),
+            TimePeriod::Night,
+            1.0,
+        ));
+
+        assert_eq!(
+            1.0,
+            percentile_daily_drinks(&entries, 0, &DrinkStandard::default())
+        );
+        assert_eq!(
+            3.5,
+            percentile_daily_drinks(&entries, 25, &DrinkStandard::default())
+        );
+        assert_eq!(
+            6.0,
+            percentile_daily_drinks(&entries, 50, &DrinkStandard::default())
+        );
+        assert_eq!(
+            8.5,
+            percentile_daily_drinks(&entries, 75, &DrinkStandard::default())
+        );
+        assert_eq!(
+            11.0,
+            percentile_daily_drinks(&entries, 100, &DrinkStandard::default())
+        );
+    }
+
+    #[test]
+    fn test_percentile_daily_drinks_single_day() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![make_entry(date, TimePeriod::Evening, 3.0)];
+
+        assert_eq!(
+            3.0,
+            percentile_daily_drinks(&entries, 0, &DrinkStandard::default())
+        );
+        assert_eq!(
+            3.0,
+            percentile_daily_drinks(&entries, 50, &DrinkStandard::default())
+        );
+        assert_eq!(
+            3.0,
+            percentile_daily_drinks(&entries, 100, &DrinkStandard::default())
+        );
+        assert_eq!(
+            0.0,
+            percentile_daily_drinks(&[], 50, &DrinkStandard::default())
+        );
+    }
+
+    fn make_context_entry(context: &[&str]) -> Entry {
+        let mut entry = make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Evening, 1.0);
+        entry.context = context.iter().map(|c| c.to_string()).collect();
+        entry
+    }
+
+    #[test]
+    fn test_context_breakdown() {
+        let entries = vec![
+            make_context_entry(&["home", "dinner"]),
+            make_context_entry(&["bar"]),
+            make_context_entry(&["home"]),
+            make_context_entry(&[]),
+            make_context_entry(&["home", "bar"]),
+        ];
+
+        let breakdown = context_breakdown(&entries);
+        assert_eq!(3, breakdown.len());
+        assert_eq!(Some(&3), breakdown.get("home"));
+        assert_eq!(Some(&2), breakdown.get("bar"));
+        assert_eq!(Some(&1), breakdown.get("dinner"));
+
+        let count = |context: &str, count| ContextCount {
+            context: context.into(),
+            count,
+        };
+        assert_eq!(
+            vec![count("home", 3), count("bar", 2), count("dinner", 1)],
+            context_counts(&entries, 0)
+        );
+        assert_eq!(
+            vec![count("home", 3), count("bar", 2)],
+            context_counts(&entries, 2)
+        );
+    }
+
+    #[test]
+    fn test_context_breakdown_without_context() {
+        let entries = vec![make_context_entry(&[]), make_context_entry(&[])];
+
+        assert!(context_breakdown(&entries).is_empty());
+        assert!(context_counts(&entries, 0).is_empty());
+        assert!(context_counts(&[], 0).is_empty());
+    }
+
+    fn make_abv_entry(min_abv: f32, max_abv: f32) -> Entry {
+        let mut entry = make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Evening, 1.0);
+        entry.min_abv = Some(ApproxF32::new(min_abv, false));
+        entry.max_abv = Some(ApproxF32::new(max_abv, false));
+        entry
+    }
+
+    #[test]
+    fn test_abv_profile() {
+        let entries = vec![
+            make_abv_entry(2.0, 3.0),
+            make_abv_entry(4.0, 5.0),
+            make_abv_entry(4.0, 5.0),
+            make_abv_entry(4.0, 5.0),
+            make_abv_entry(5.0, 6.0),
+            make_abv_entry(5.0, 6.0),
+            make_abv_entry(7.0, 8.0),
+            make_abv_entry(9.0, 10.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 4), TimePeriod::Evening, 1.0),
+        ];
+
+        let profile = abv_profile(&entries);
+
+        assert_eq!(5.0, profile.mean_min_abv);
+        assert_eq!(6.0, profile.mean_max_abv);
+        assert!((profile.std_dev_abv - 2.0).abs() < 0.0001);
+        assert_eq!(1, profile.entries_without_abv);
+        assert_eq!(8, profile.entries_with_abv);
+        assert!(profile.is_reliable());
+    }
+
+    #[test]
+    fn test_abv_profile_identical() {
+        let entries = vec![make_abv_entry(5.0, 5.0), make_abv_entry(5.0, 5.0)];
+
+        let profile = abv_profile(&entries);
+
+        assert_eq!(5.0, profile.mean_min_abv);
+        assert_eq!(0.0, profile.std_dev_abv);
+    }
+
+    #[test]
+    fn test_abv_profile_without_abv() {
+        let date = NaiveDate::from_ymd(2020, 5, 4);
+        let entries = vec![
+            make_abv_entry(5.0, 5.0),
+            make_entry(date, TimePeriod::Evening, 1.0),
+        ];
+
+        let profile = abv_profile(&entries);
+
+        assert_eq!(5.0, profile.mean_min_abv);
+        assert_eq!(1, profile.entries_without_abv);
+        assert!(!profile.is_reliable());
+
+        let empty = abv_profile(&[]);
+        assert_eq!(0.0, empty.mean_min_abv);
+        assert_eq!(0.0, empty.std_dev_abv);
+        assert!(!empty.is_reliable());
+    }
+
+    #[test]
+    fn test_abv_timeline() {
+        let entry = |year, month, day, min_abv, max_abv| {
+            let mut entry = make_abv_entry(min_abv, max_abv);
+            entry.drank_on = NaiveDate::from_ymd(year, month, day);
+            entry
+        };
+
+        let entries = vec![
+            entry(2020, 5, 4, 4.0, 5.0),
+            entry(2020, 5, 20, 6.0, 7.0),
+            entry(2019, 12, 30, 10.0, 12.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 5), TimePeriod::Evening, 1.0),
+        ];
+
+        let monthly = abv_timeline(&entries, "month");
+        assert_eq!(
+            vec![
+                AbvTimelinePoint {
+                    label: "2019-12".into(),
+                    avg_min_abv: 10.0,
+                    avg_max_abv: 12.0,
+                    entry_count: 1,
+                },
+                AbvTimelinePoint {
+                    label: "2020-05".into(),
+                    avg_min_abv: 5.0,
+                    avg_max_abv: 6.0,
+                    entry_count: 2,
+                },
+            ],
+            monthly
+        );
+
+        // 2019-12-30 falls within the first ISO week of 2020.
+        let weekly = abv_timeline(&entries, "week");
+        let labels: Vec<&str> = weekly.iter().map(|point| point.label.as_str()).collect();
+        assert_eq!(vec!["2020-W01", "2020-W19", "2020-W21"], labels);
+        assert_eq!(4.0, weekly[1].avg_min_abv);
+        assert_eq!(1, weekly[1].entry_count);
+
+        assert!(abv_timeline(&entries[3..], "month").is_empty());
+    }
+
+    #[test]
+    fn test_entry_frequency_histogram() {
+        let day = |d| make_entry(NaiveDate::from_ymd(2020, 5, d), TimePeriod::Evening, 1.0);
+
+        // Gaps of 1, 2, 3, 1 and 7 days; the duplicate day is not a gap.
+        let entries = vec![day(1), day(2), day(4), day(4), day(7), day(8), day(15)];
+
+        let histogram = entry_frequency_histogram(&entries, 2);
+        let counts: Vec<(u32, u32, u32)> = histogram
+            .iter()
+            .map(|bucket| (bucket.gap_days_start, bucket.gap_days_end, bucket.count))
+            .collect();
+        assert_eq!(vec![(0, 1, 2), (2, 3, 2), (4, 5, 0), (6, 7, 1)], counts);
+
+        let daily = entry_frequency_histogram(&entries, 1);
+        assert_eq!(8, daily.len());
+        assert_eq!(2, daily[1].count);
+        assert_eq!(1, daily[7].count);
+
+        // Zero-width buckets are treated as one day wide.
+        assert_eq!(daily, entry_frequency_histogram(&entries, 0));
+
+        assert!(entry_frequency_histogram(&entries[..1], 2).is_empty());
+        assert!(entry_frequency_histogram(&[], 2).is_empty());
+    }
+
+    #[test]
+    fn test_drinking_sessions() {
+        let entry = |day, time, quantity, context: &[&str]| {
+            let mut entry = make_entry(NaiveDate::from_ymd(2020, 5, day), time, quantity);
+            entry.context = context.iter().map(|tag| tag.to_string()).collect();
+            entry
+        };
+
+        let entries = vec![
+            entry(1, TimePeriod::Evening, 2.0, &["pub", "friends"]),
+            entry(2, TimePeriod::Afternoon, 1.0, &[]),
+            entry(1, TimePeriod::Evening, 1.0, &["friends", "birthday"]),
+            entry(1, TimePeriod::Night, 3.0, &["home"]),
+        ];
+
+        let sessions = drinking_sessions(entries, &DrinkStandard::default());
+        let keys: Vec<(u32, TimePeriod, usize)> = sessions
+            .iter()
+            .map(|session| {
+                (
+                    session.date.day(),
+                    session.time_period,
+                    session.entries.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (2, TimePeriod::Afternoon, 1),
+                (1, TimePeriod::Night, 1),
+                (1, TimePeriod::Evening, 2),
+            ],
+            keys
+        );
+
+        let evening = &sessions[2];
+        assert_eq!(vec!["pub", "friends", "birthday"], evening.context);
+        assert_eq!(3.0, evening.total.min_drinks);
+        assert_eq!(3.0, evening.total.max_drinks);
+        assert!(sessions[0].context.is_empty());
+
+        assert!(drinking_sessions(Vec::new(), &DrinkStandard::default()).is_empty());
+    }
+
+    #[test]
+    fn test_calendar_matrix_for_month() {
+        let entries = vec![
+            make_entry(NaiveDate::from_ymd(2020, 4, 30), TimePeriod::Evening, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 1), TimePeriod::Evening, 2.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 1), TimePeriod::Night, 1.0),
+            make_entry(NaiveDate::from_ymd(2020, 5, 31), TimePeriod::Evening, 1.0),
+        ];
+
+        // May 2020 starts on a Friday and ends on a Sunday.
+        let calendar = CalendarMatrix::for_month(2020, 5, &entries, &DrinkStandard::default());
+        assert_eq!((2020, 5), (calendar.year, calendar.month));
+        assert_eq!(5, calendar.weeks.len());
+        assert!(calendar.weeks.iter().all(|week| week.len() == 7));
+
+        let first_week = &calendar.weeks[0];
+        assert!(first_week[..4].iter().all(|day| day.is_none()));
+        let may_1 = first_week[4].as_ref().unwrap();
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 1), may_1.date);
+        assert_eq!(2, may_1.entry_count);
+        assert_eq!(3.0, may_1.min_drinks);
+        assert_eq!(0, first_week[5].as_ref().unwrap().entry_count);
+
+        let last_week = &calendar.weeks[4];
+        let may_31 = last_week[6].as_ref().unwrap();
+        assert_eq!(NaiveDate::from_ymd(2020, 5, 31), may_31.date);
+        assert_eq!(1, may_31.entry_count);
+
+        // June 2020 starts on a Monday, but ends on a Tuesday.
+        let calendar = CalendarMatrix::for_month(2020, 6, &entries, &DrinkStandard::default());
+        assert_eq!(5, calendar.weeks.len());
+        assert!(calendar.weeks[0][0].is_some());
+        let last_week = &calendar.weeks[4];
+        assert!(last_week[..2].iter().all(|day| day.is_some()));
+        assert!(last_week[2..].iter().all(|day| day.is_none()));
+        assert!(calendar
+            .weeks
+            .iter()
+            .flatten()
+            .flatten()
+            .all(|day| day.entry_count == 0));
+
+        // December rolls over into the following year.
+        let calendar = CalendarMatrix::for_month(2019, 12, &[], &DrinkStandard::default());
+        let days = calendar.weeks.iter().flatten().flatten().count();
+        assert_eq!(31, days);
+    }
+}
@@ -50,6 +50,21 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use super::{Realapprox, Timeperiod, Volumeunit, Volume};
+
+    entry_audit_log (id) {
+        id -> Int4,
+        entry_id -> Int4,
+        changed_at -> Timestamptz,
+        field_name -> Varchar,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        changed_by -> Int4,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use super::{Realapprox, Timeperiod, Volumeunit, Volume};
@@ -58,10 +73,14 @@ table! {
         id -> Int4,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        username -> Nullable<Varchar>,
+        password_hash -> Nullable<Varchar>,
     }
 }
 
 joinable!(entry -> drink (drink_id));
 joinable!(entry -> person (person_id));
+joinable!(entry_audit_log -> entry (entry_id));
+joinable!(entry_audit_log -> person (changed_by));
 
-allow_tables_to_appear_in_same_query!(drink, entry, person,);
+allow_tables_to_appear_in_same_query!(drink, entry, entry_audit_log, person,);